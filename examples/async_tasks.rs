@@ -0,0 +1,106 @@
+use ratatui::{backend::Backend, widgets::Paragraph, Frame};
+use std::time::Duration;
+use tui_utils::{
+    async_loop::{AsyncEventLoop, Tasks},
+    blocks,
+    component::{Component, ComponentError},
+    event_loop::Flow,
+    keys::{key_match, KeyCode, KeyEvent, KeyModifiers, Keybind},
+    term,
+};
+
+struct KeyBinds {
+    quit: Keybind,
+    fetch: Keybind,
+}
+
+impl KeyBinds {
+    fn new() -> Self {
+        Self {
+            quit: Keybind::new(KeyCode::Esc, KeyModifiers::empty()),
+            fetch: Keybind::new(KeyCode::Enter, KeyModifiers::empty()),
+        }
+    }
+}
+
+#[derive(Default)]
+enum AppMessage {
+    #[default]
+    Idle,
+    // produced by the spawned future once it completes
+    Fetched(String),
+    Exit,
+}
+
+struct Fetcher {
+    text: String,
+    fetches: usize,
+    tasks: Tasks<AppMessage>,
+    binds: KeyBinds,
+}
+
+impl Component for Fetcher {
+    type Message = AppMessage;
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, _dim: bool) {
+        let p = Paragraph::new(self.text.as_str())
+            .block(blocks::themed_default_block("Press enter to fetch"));
+        f.render_widget(p, f.size());
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> Result<Self::Message, ComponentError> {
+        if key_match(&key, &self.binds.quit) {
+            return Ok(AppMessage::Exit);
+        } else if key_match(&key, &self.binds.fetch) {
+            self.fetches += 1;
+            self.text = String::from("fetching...");
+            let n = self.fetches;
+            // pretend this is a slow IPC call, the ui keeps running meanwhile
+            self.tasks.spawn(async move {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                AppMessage::Fetched(format!("result of fetch #{n}"))
+            });
+        }
+        Ok(AppMessage::Idle)
+    }
+
+    fn unmount(&mut self) {
+        // don't leave any work running once the component is gone
+        self.tasks.cancel();
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let event_loop = AsyncEventLoop::default();
+    let mut fetcher = Fetcher {
+        text: String::new(),
+        fetches: 0,
+        tasks: event_loop.tasks(),
+        binds: KeyBinds::new(),
+    };
+
+    // init the terminal
+    let mut terminal = term::init().unwrap();
+
+    let outcome = event_loop
+        .run(
+            &mut terminal,
+            &mut fetcher,
+            |fetcher, message| match message {
+                AppMessage::Fetched(text) => {
+                    fetcher.text = text;
+                    Flow::Continue
+                }
+                AppMessage::Idle => Flow::Continue,
+                AppMessage::Exit => Flow::Exit,
+            },
+        )
+        .await;
+
+    // the guard restores the terminal when dropped, but it has to happen
+    // before printing the error so the message is readable
+    if let Err(e) = outcome {
+        terminal.restore().unwrap();
+        eprintln!("Application error: {e}");
+    }
+}
@@ -1,16 +1,14 @@
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     backend::Backend,
     layout::Rect,
-    style::Color,
-    widgets::{Clear, List, ListItem},
+    widgets::{Clear, List, ListItem, Paragraph},
     Frame,
 };
-use std::error::Error;
 use tui_utils::{
     blocks,
-    component::Component,
-    keys::{key_match, Keybind},
+    component::{Component, ComponentError},
+    event::Event,
+    keys::{key_match, KeyCode, KeyEvent, KeyModifiers, Keybind},
     state::{Boundary, BoundedState, StateWrap},
     style, term, LIST_HIGHLIGHT_SYMBOL,
 };
@@ -71,8 +69,13 @@ struct View {
     items: Vec<String>,
     state: BoundedState,
     binds: KeyBinds,
+    // last recoverable error, shown at the bottom of the screen
+    status: Option<String>,
 }
 
+// adding items past this limit produces a recoverable error
+const MAX_ITEMS: usize = 40;
+
 impl Component for View {
     type Message = AppMessage;
     fn draw<B: Backend>(&mut self, f: &mut Frame<B>, _dim: bool) {
@@ -88,7 +91,7 @@ impl Component for View {
 
         // create the list with `tui_utils` helpers
         let list = List::new(items)
-            .block(blocks::default_block("List", Color::White))
+            .block(blocks::themed_default_block("List"))
             .highlight_style(style::highlight_style())
             .highlight_symbol(LIST_HIGHLIGHT_SYMBOL);
 
@@ -116,13 +119,26 @@ impl Component for View {
             height: keybind_items.len() as u16 + 2, // take the number of items as a height guide and account for borders
         };
 
-        let help_list = List::new(keybind_items).block(blocks::default_block("Help", Color::White));
+        let help_list = List::new(keybind_items).block(blocks::themed_default_block("Help"));
         // clear the space where the help screen will be rendered
         f.render_widget(Clear, help_rect);
         f.render_widget(help_list, help_rect);
+
+        // show the last recoverable error on the bottom border
+        if let Some(status) = &self.status {
+            let status_rect = Rect {
+                x: 2,
+                y: size.height - 1,
+                width: size.width.saturating_sub(4),
+                height: 1,
+            };
+            f.render_widget(Paragraph::new(status.as_str()), status_rect);
+        }
     }
 
-    fn handle_input(&mut self, key: KeyEvent) -> Result<Self::Message, Box<dyn Error>> {
+    fn handle_input(&mut self, key: KeyEvent) -> Result<Self::Message, ComponentError> {
+        // any new input clears the previous error
+        self.status = None;
         if key_match(&key, &self.binds.quit) {
             // signal an exit if the quit bind is pressed
             return Ok(AppMessage::Exit);
@@ -131,6 +147,10 @@ impl Component for View {
         } else if key_match(&key, &self.binds.down) {
             self.state.next();
         } else if key_match(&key, &self.binds.add) {
+            if self.items.len() >= MAX_ITEMS {
+                // the app can keep running, so report it as recoverable
+                return Err(ComponentError::recoverable("the list is full"));
+            }
             // add a new item to the `items` member
             self.items.push(format!("Item {}", self.items.len() + 1));
             // update the boundary accordingly
@@ -173,6 +193,7 @@ fn main() {
         items,
         state,
         binds: KeyBinds::new(),
+        status: None,
     };
     let mut app = App { view };
 
@@ -197,13 +218,18 @@ fn main() {
         match event_outcome {
             Ok(AppMessage::Idle) => {}
             Ok(AppMessage::Exit) => break, // exit on signal
+            // recoverable errors are shown in the status line while fatal
+            // ones end the application
             Err(e) => {
-                term::restore_with_err(e).unwrap();
-                return;
+                if let Err(e) = e.route(|e| app.view.status = Some(e.to_string())) {
+                    // restore the terminal before printing the error
+                    drop(terminal);
+                    eprintln!("Application error: {e}");
+                    return;
+                }
             }
         }
     }
 
-    // always restore the terminal before exiting
-    term::restore().unwrap()
+    // the terminal is restored when `terminal` goes out of scope
 }
@@ -1,15 +1,14 @@
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     backend::Backend,
-    style::Color,
     widgets::{Clear, Paragraph},
     Frame,
 };
-use std::{error::Error, rc::Rc};
+use std::rc::Rc;
 use tui_utils::{
     blocks::{self, Dim},
-    component::Component,
-    keys::{key_match, Keybind},
+    component::{Component, ComponentError},
+    event::Event,
+    keys::{key_match, KeyCode, KeyEvent, KeyModifiers, Keybind},
     rect,
     shared::Shared,
     term,
@@ -71,13 +70,13 @@ struct Modal {
 
 impl Component for Main {
     type Message = AppMessage;
-    fn draw<B: tui::backend::Backend>(&mut self, f: &mut tui::Frame<B>, dim: bool) {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, dim: bool) {
         let p = Paragraph::new("This is the main component. Press space to open modal.")
-            .block(blocks::default_block("Main", Color::White).dim(dim));
+            .block(blocks::themed_default_block("Main").dim(dim));
         f.render_widget(p, f.size());
     }
 
-    fn handle_input(&mut self, key: KeyEvent) -> Result<Self::Message, Box<dyn Error>> {
+    fn handle_input(&mut self, key: KeyEvent) -> Result<Self::Message, ComponentError> {
         if key_match(&key, &self.keys.quit) {
             // return exit signal if the quit key is pressed
             return Ok(AppMessage::Exit);
@@ -93,8 +92,7 @@ impl Component for Modal {
     type Message = AppMessage;
     fn draw<B: Backend>(&mut self, f: &mut Frame<B>, _dim: bool) {
         let rect = rect::centered_rect(f.size());
-        let p =
-            Paragraph::new("This is the modal").block(blocks::default_block("Modal", Color::White));
+        let p = Paragraph::new("This is the modal").block(blocks::themed_default_block("Modal"));
 
         // clear the space the modal will take
         f.render_widget(Clear, rect);
@@ -103,7 +101,7 @@ impl Component for Modal {
         f.render_widget(p, rect);
     }
 
-    fn handle_input(&mut self, key: KeyEvent) -> Result<Self::Message, Box<dyn Error>> {
+    fn handle_input(&mut self, key: KeyEvent) -> Result<Self::Message, ComponentError> {
         if key_match(&key, &self.keys.quit) {
             // return exit signal if the quit key is pressed
             return Ok(AppMessage::Back);
@@ -165,12 +163,13 @@ fn main() {
             Ok(AppMessage::Exit) => break,
             Ok(AppMessage::Idle) => {}
             Err(e) => {
-                term::restore_with_err(e).unwrap();
+                // restore before printing so the message is readable
+                terminal.restore().unwrap();
+                eprintln!("Application error: {e}");
                 return;
             }
         }
     }
 
-    // always restore the terminal before exiting
-    term::restore().unwrap()
+    // the terminal is restored when `terminal` goes out of scope
 }
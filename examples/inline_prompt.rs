@@ -0,0 +1,65 @@
+use ratatui::{backend::Backend, widgets::Paragraph, Frame};
+use tui_utils::{
+    blocks,
+    component::{Component, ComponentError},
+    event_loop::{EventLoop, Flow},
+    keys::{KeyCode, KeyEvent},
+    term::{InitOptions, InlineExit},
+};
+
+#[derive(Default)]
+enum AppMessage {
+    #[default]
+    Idle,
+    Answer(bool),
+}
+
+struct Prompt {
+    question: &'static str,
+}
+
+impl Component for Prompt {
+    type Message = AppMessage;
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>, _dim: bool) {
+        // the frame only covers the reserved lines
+        let p = Paragraph::new(format!("{} [y/n]", self.question))
+            .block(blocks::themed_default_block("Prompt"));
+        f.render_widget(p, f.size());
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> Result<Self::Message, ComponentError> {
+        match key.code {
+            KeyCode::Char('y') => Ok(AppMessage::Answer(true)),
+            KeyCode::Char('n') | KeyCode::Esc => Ok(AppMessage::Answer(false)),
+            _ => Ok(AppMessage::Idle),
+        }
+    }
+}
+
+fn main() {
+    // render into three lines below the shell prompt and clear them on exit
+    let mut terminal = InitOptions::default()
+        .inline(3)
+        .inline_exit(InlineExit::Clear)
+        .init()
+        .unwrap();
+
+    let mut prompt = Prompt {
+        question: "Delete all the things?",
+    };
+    let mut answer = false;
+    let outcome =
+        EventLoop::default().run(&mut terminal, &mut prompt, |_, message| match message {
+            AppMessage::Idle => Flow::Continue,
+            AppMessage::Answer(a) => {
+                answer = a;
+                Flow::Exit
+            }
+        });
+
+    terminal.restore().unwrap();
+    match outcome {
+        Ok(()) => println!("answer: {answer}"),
+        Err(e) => eprintln!("Application error: {e}"),
+    }
+}
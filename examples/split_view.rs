@@ -1,10 +1,10 @@
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
-use ratatui::{backend::Backend, style::Color, widgets::Paragraph, Frame};
-use std::error::Error;
+use ratatui::{backend::Backend, widgets::Paragraph, Frame};
+use std::time::Duration;
 use tui_utils::{
     blocks,
-    component::Component,
-    keys::{key_match, Keybind},
+    component::{Component, ComponentError},
+    event_loop::{EventLoop, Flow},
+    keys::{key_match, KeyCode, KeyEvent, KeyModifiers, Keybind},
     split::{self, Ratio},
     term,
 };
@@ -27,10 +27,6 @@ impl KeyBinds {
     }
 }
 
-struct App {
-    split: Split,
-}
-
 #[derive(Default)]
 enum AppMessage {
     #[default]
@@ -41,6 +37,8 @@ enum AppMessage {
 struct Split {
     text_left: String,
     text_right: String,
+    // time spent since the component was mounted
+    uptime: Duration,
     binds: KeyBinds,
 }
 
@@ -53,17 +51,16 @@ impl Component for Split {
         let chunks = split::v_split(f.size(), ratio);
 
         // paragraphs as an example using default blocks
-        let left_p =
-            Paragraph::new(&*self.text_left).block(blocks::default_block("Left", Color::White));
-        let right_p =
-            Paragraph::new(&*self.text_right).block(blocks::default_block("Right", Color::White));
+        let left_p = Paragraph::new(&*self.text_left).block(blocks::themed_default_block("Left"));
+        let right_text = format!("{}\nuptime: {}s", self.text_right, self.uptime.as_secs());
+        let right_p = Paragraph::new(right_text).block(blocks::themed_default_block("Right"));
 
         // render the widgets
         f.render_widget(left_p, chunks[0]);
         f.render_widget(right_p, chunks[1]);
     }
 
-    fn handle_input(&mut self, key: KeyEvent) -> Result<Self::Message, Box<dyn Error>> {
+    fn handle_input(&mut self, key: KeyEvent) -> Result<Self::Message, ComponentError> {
         if key_match(&key, &self.binds.quit) {
             // send back an exit signal if the quit key is pressed
             return Ok(AppMessage::Exit);
@@ -71,44 +68,44 @@ impl Component for Split {
         // do nothing by default
         Ok(AppMessage::Idle)
     }
+
+    fn mount(&mut self) {
+        self.uptime = Duration::ZERO;
+    }
+
+    fn tick(&mut self, elapsed: Duration) -> Self::Message {
+        // the event loop calls this periodically with the time since the last tick
+        self.uptime += elapsed;
+        AppMessage::Idle
+    }
 }
 
 fn main() {
     // init the terminal
     let mut terminal = term::init().unwrap();
 
-    let split = Split {
+    let mut split = Split {
         text_left: String::from("left side"),
         text_right: String::from("right side"),
+        uptime: Duration::ZERO,
         binds: KeyBinds::new(),
     };
-    let mut app = App { split };
-
-    loop {
-        // draw the ui first
-        terminal.draw(|f| app.split.draw(f, false)).unwrap();
 
-        // then handle input events
-        let event_outcome = match term::poll_event() {
-            Ok(Some(Event::Key(ev))) => app.split.handle_input(ev),
-            // other term events, we dont handle them in this example
-            Ok(Some(_)) => Ok(AppMessage::Idle),
-            // no events were found
-            Ok(None) => Ok(AppMessage::Idle),
-            // something went wrong
-            Err(e) => Err(e.into()),
-        };
+    // the event loop draws the component, forwards input and ticks it.
+    // it also suspends the app to the shell on ctrl+z
+    let outcome = EventLoop::default().suspend(true).run(
+        &mut terminal,
+        &mut split,
+        |_, message| match message {
+            AppMessage::Idle => Flow::Continue,
+            AppMessage::Exit => Flow::Exit,
+        },
+    );
 
-        match event_outcome {
-            Ok(AppMessage::Idle) => {}
-            Ok(AppMessage::Exit) => break,
-            Err(e) => {
-                term::restore_with_err(e).unwrap();
-                return;
-            }
-        }
+    // the guard restores the terminal when dropped, but it has to happen
+    // before printing the error so the message is readable
+    if let Err(e) = outcome {
+        terminal.restore().unwrap();
+        eprintln!("Application error: {e}");
     }
-
-    // always restore the terminal before exiting
-    term::restore().unwrap()
 }
@@ -0,0 +1,394 @@
+use std::{
+    future::Future,
+    io::{self, Write},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{Event, EventStream};
+use futures_util::{Stream, StreamExt};
+use ratatui::backend::CrosstermBackend;
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::{AbortHandle, JoinHandle},
+    time,
+};
+
+use crate::{
+    component::{Component, ComponentError},
+    event_loop::{dispatch, is_suspend, Flow, DEFAULT_SUSPEND_KEY},
+    keys::Keybind,
+    term::{TermError, TerminalGuard},
+    EVENT_TIMEOUT,
+};
+
+/// Spawns futures whose output is delivered to the event loop as messages.
+/// All tasks still running are aborted when `cancel` is called or the
+/// handle is dropped, so components should call `cancel` in `unmount`.
+/// Tasks left running when `AsyncEventLoop::run` returns are aborted then.
+pub struct Tasks<M> {
+    sender: UnboundedSender<M>,
+    handles: Vec<JoinHandle<()>>,
+    /// Every task spawned through the loop, aborted when it stops
+    spawned: Spawned,
+}
+
+/// Abort handles shared by a loop and its `Tasks`
+type Spawned = Arc<Mutex<Vec<AbortHandle>>>;
+
+impl<M: Send + 'static> Tasks<M> {
+    /// Spawn `future` on the tokio runtime. Its output is sent to the event
+    /// loop which passes it to the message handler like any other message.
+    pub fn spawn<F>(&mut self, future: F)
+    where
+        F: Future<Output = M> + Send + 'static,
+    {
+        self.handles.retain(|h| !h.is_finished());
+        let sender = self.sender.clone();
+        let handle = tokio::spawn(async move {
+            // the loop is gone if this fails, nobody is left to care
+            let _ = sender.send(future.await);
+        });
+        let mut spawned = self.spawned.lock().unwrap_or_else(PoisonError::into_inner);
+        spawned.retain(|h| !h.is_finished());
+        spawned.push(handle.abort_handle());
+        self.handles.push(handle);
+    }
+}
+
+impl<M> Tasks<M> {
+    /// Abort every task that is still running
+    pub fn cancel(&mut self) {
+        for handle in self.handles.drain(..) {
+            handle.abort();
+        }
+    }
+
+    /// Number of tasks that have not finished yet
+    pub fn running(&self) -> usize {
+        self.handles.iter().filter(|h| !h.is_finished()).count()
+    }
+}
+
+impl<M> Drop for Tasks<M> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Async counterpart of `EventLoop` which reads terminal events from
+/// crossterm's `EventStream` and also delivers messages produced by
+/// futures spawned through `Tasks`. It runs on the crossterm backend only.
+/// ```no_run
+/// # async fn run() -> Result<(), tui_utils::component::ComponentError> {
+/// # struct App;
+/// # impl tui_utils::component::Component for App {
+/// #     type Message = ();
+/// #     fn draw<B: ratatui::backend::Backend>(&mut self, _: &mut ratatui::Frame<B>, _: bool) {}
+/// # }
+/// use tui_utils::{async_loop::AsyncEventLoop, event_loop::Flow, term};
+///
+/// let mut terminal = term::init()?;
+/// let event_loop = AsyncEventLoop::default();
+/// // hand `tasks` to the components that need to spawn futures
+/// let tasks = event_loop.tasks();
+/// event_loop.run(&mut terminal, &mut App, |_, _| Flow::Continue).await
+/// # }
+/// ```
+pub struct AsyncEventLoop<M> {
+    tick_rate: Duration,
+    suspend_key: Option<Keybind>,
+    sender: UnboundedSender<M>,
+    receiver: UnboundedReceiver<M>,
+    spawned: Spawned,
+}
+
+impl<M: Send + 'static> Default for AsyncEventLoop<M> {
+    fn default() -> Self {
+        Self::new(EVENT_TIMEOUT)
+    }
+}
+
+impl<M: Send + 'static> AsyncEventLoop<M> {
+    /// Creates an event loop which ticks the component every `tick_rate`
+    pub fn new(tick_rate: Duration) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            tick_rate,
+            suspend_key: None,
+            sender,
+            receiver,
+            spawned: Spawned::default(),
+        }
+    }
+
+    /// Suspend the application on Ctrl+Z like shells do, see `TerminalGuard::suspend`.
+    /// The key press is not forwarded to the component.
+    pub fn suspend(self, enable: bool) -> Self {
+        self.suspend_key(enable.then_some(DEFAULT_SUSPEND_KEY))
+    }
+
+    /// Like `suspend`, but with a custom binding
+    pub fn suspend_key(mut self, key: Option<Keybind>) -> Self {
+        self.suspend_key = key;
+        self
+    }
+
+    /// Create a handle for spawning futures which report back to this loop
+    pub fn tasks(&self) -> Tasks<M> {
+        Tasks {
+            sender: self.sender.clone(),
+            handles: vec![],
+            spawned: self.spawned.clone(),
+        }
+    }
+
+    /// Run the loop until `update` returns `Flow::Exit` or a fatal error occurs.
+    /// Messages from the component and from spawned futures are passed to
+    /// `update` together with the component itself. The first tick comes
+    /// after `tick_rate` has passed. Tasks still running after the component
+    /// was unmounted are aborted.
+    pub async fn run<W, C, F>(
+        self,
+        terminal: &mut TerminalGuard<CrosstermBackend<W>>,
+        component: &mut C,
+        update: F,
+    ) -> Result<(), ComponentError>
+    where
+        W: Write,
+        C: Component<Message = M>,
+        F: FnMut(&mut C, C::Message) -> Flow,
+    {
+        self.run_on(terminal, component, update, EventStream::new())
+            .await
+    }
+
+    async fn run_on<W, C, F, S>(
+        mut self,
+        terminal: &mut TerminalGuard<CrosstermBackend<W>>,
+        component: &mut C,
+        mut update: F,
+        events: S,
+    ) -> Result<(), ComponentError>
+    where
+        W: Write,
+        C: Component<Message = M>,
+        F: FnMut(&mut C, C::Message) -> Flow,
+        S: Stream<Item = io::Result<Event>> + Unpin,
+    {
+        component.mount();
+        let outcome = self.drive(terminal, component, &mut update, events).await;
+        component.unmount();
+        self.abort_tasks();
+        outcome
+    }
+
+    /// Abort every task spawned through this loop's `Tasks`
+    fn abort_tasks(&self) {
+        let spawned = self.spawned.lock().unwrap_or_else(PoisonError::into_inner);
+        for handle in spawned.iter() {
+            handle.abort();
+        }
+    }
+
+    async fn drive<W, C, F, S>(
+        &mut self,
+        terminal: &mut TerminalGuard<CrosstermBackend<W>>,
+        component: &mut C,
+        update: &mut F,
+        mut events: S,
+    ) -> Result<(), ComponentError>
+    where
+        W: Write,
+        C: Component<Message = M>,
+        F: FnMut(&mut C, C::Message) -> Flow,
+        S: Stream<Item = io::Result<Event>> + Unpin,
+    {
+        // `interval` would tick right away
+        let start = time::Instant::now() + self.tick_rate;
+        let mut ticker = time::interval_at(start, self.tick_rate);
+        let mut last_tick = Instant::now();
+        loop {
+            terminal.draw(|f| component.draw(f, false))?;
+
+            let message = tokio::select! {
+                event = events.next() => match event {
+                    Some(Ok(Event::Key(key))) if is_suspend(self.suspend_key, &key.into()) => {
+                        terminal.suspend()?;
+                        None
+                    }
+                    Some(Ok(event)) => dispatch(component, event.into())?,
+                    Some(Err(e)) => return Err(TermError::Poll(e.to_string()).into()),
+                    // the event stream never ends while the terminal is open
+                    None => return Ok(()),
+                },
+                // the loop holds a sender itself so this is never `None`
+                Some(message) = self.receiver.recv() => Some(message),
+                _ = ticker.tick() => {
+                    let elapsed = last_tick.elapsed();
+                    last_tick = Instant::now();
+                    Some(component.tick(elapsed))
+                }
+            };
+
+            if let Some(message) = message {
+                if update(component, message) == Flow::Exit {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, time::Duration};
+
+    use futures_util::stream;
+    use ratatui::{
+        backend::{Backend, CrosstermBackend},
+        Frame,
+    };
+    use tokio::{sync::oneshot, time};
+
+    use super::{AsyncEventLoop, Tasks};
+    use crate::{
+        component::Component,
+        event_loop::Flow,
+        term::{InitOptions, TerminalGuard},
+    };
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    enum Message {
+        #[default]
+        Idle,
+        Done,
+        Ticked,
+    }
+
+    /// Spawns a task finishing right away and one running until it is
+    /// aborted when mounted
+    struct Worker {
+        tasks: Tasks<Message>,
+        alive: Option<oneshot::Sender<()>>,
+        calls: Vec<&'static str>,
+        ticks: Vec<Duration>,
+    }
+
+    impl Worker {
+        fn new(tasks: Tasks<Message>, alive: oneshot::Sender<()>) -> Self {
+            Self {
+                tasks,
+                alive: Some(alive),
+                calls: vec![],
+                ticks: vec![],
+            }
+        }
+    }
+
+    impl Component for Worker {
+        type Message = Message;
+
+        fn draw<B: Backend>(&mut self, _f: &mut Frame<B>, _dim: bool) {}
+
+        fn mount(&mut self) {
+            self.calls.push("mount");
+            self.tasks.spawn(async { Message::Done });
+            let alive = self.alive.take();
+            self.tasks.spawn(async move {
+                let _alive = alive;
+                time::sleep(Duration::from_secs(60)).await;
+                Message::Idle
+            });
+        }
+
+        fn unmount(&mut self) {
+            self.calls.push("unmount");
+        }
+
+        fn tick(&mut self, elapsed: Duration) -> Message {
+            self.ticks.push(elapsed);
+            Message::Ticked
+        }
+    }
+
+    /// Run `event_loop` without terminal input until `update` gets `exit_on`
+    async fn run(event_loop: AsyncEventLoop<Message>, component: &mut Worker, exit_on: Message) {
+        let backend = CrosstermBackend::new(io::sink());
+        let mut terminal = TerminalGuard::new(backend, InitOptions::none()).unwrap();
+        let update = move |_: &mut Worker, message| match message == exit_on {
+            true => Flow::Exit,
+            false => Flow::Continue,
+        };
+        event_loop
+            .run_on(&mut terminal, component, update, stream::pending())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn task_output_reaches_update() {
+        let event_loop = AsyncEventLoop::new(Duration::from_secs(60));
+        let (alive, stopped) = oneshot::channel();
+        let mut worker = Worker::new(event_loop.tasks(), alive);
+        run(event_loop, &mut worker, Message::Done).await;
+        assert_eq!(worker.calls, ["mount", "unmount"]);
+
+        // the task still running was aborted when the loop stopped
+        let dropped = time::timeout(Duration::from_secs(5), stopped).await;
+        assert!(matches!(dropped, Ok(Err(_))));
+    }
+
+    #[tokio::test]
+    async fn first_tick_waits_for_the_tick_rate() {
+        let tick_rate = Duration::from_millis(20);
+        let event_loop = AsyncEventLoop::new(tick_rate);
+        let (alive, _stopped) = oneshot::channel();
+        let mut worker = Worker::new(event_loop.tasks(), alive);
+        run(event_loop, &mut worker, Message::Ticked).await;
+        assert!(worker.ticks[0] >= tick_rate);
+    }
+
+    #[tokio::test]
+    async fn spawned_output_is_delivered() {
+        let mut event_loop = AsyncEventLoop::<u8>::default();
+        let mut tasks = event_loop.tasks();
+        tasks.spawn(async { 42 });
+        assert_eq!(event_loop.receiver.recv().await, Some(42));
+    }
+
+    #[tokio::test]
+    async fn cancel_aborts_running_tasks() {
+        let event_loop = AsyncEventLoop::<u8>::default();
+        let mut tasks = event_loop.tasks();
+        let (alive, stopped) = oneshot::channel::<()>();
+        tasks.spawn(async move {
+            let _alive = alive;
+            time::sleep(Duration::from_secs(60)).await;
+            1
+        });
+        assert_eq!(tasks.running(), 1);
+
+        tasks.cancel();
+        // the sender is only dropped early if the task really stopped
+        let dropped = time::timeout(Duration::from_secs(5), stopped).await;
+        assert!(matches!(dropped, Ok(Err(_))));
+        assert_eq!(tasks.running(), 0);
+    }
+
+    #[tokio::test]
+    async fn stopping_the_loop_aborts_tasks() {
+        let event_loop = AsyncEventLoop::<u8>::default();
+        let mut tasks = event_loop.tasks();
+        let (alive, stopped) = oneshot::channel::<()>();
+        tasks.spawn(async move {
+            let _alive = alive;
+            time::sleep(Duration::from_secs(60)).await;
+            1
+        });
+
+        event_loop.abort_tasks();
+        let dropped = time::timeout(Duration::from_secs(5), stopped).await;
+        assert!(matches!(dropped, Ok(Err(_))));
+    }
+}
@@ -0,0 +1,272 @@
+//! Terminal setup and input through crossterm, which also works on Windows
+
+use std::{
+    io::{self, Write},
+    time::Duration,
+};
+
+use ::crossterm::{
+    event::{
+        self as ct, DisableBracketedPaste, DisableFocusChange, DisableMouseCapture,
+        EnableBracketedPaste, EnableFocusChange, EnableMouseCapture, PopKeyboardEnhancementFlags,
+        PushKeyboardEnhancementFlags,
+    },
+    execute,
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::backend::CrosstermBackend;
+
+#[cfg(unix)]
+use super::read_tty_reply;
+use super::{EventInput, Restorer, TermBackend};
+use crate::{
+    event::{Event, MouseButton, MouseEvent, MouseEventKind},
+    keys::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    term::{KeyboardEnhancementFlags, Output, TermError},
+};
+
+impl<W: Write> TermBackend for CrosstermBackend<W> {
+    type Input = CrosstermInput;
+
+    fn write_sequence(&mut self, sequence: &str) -> io::Result<()> {
+        self.write_all(sequence.as_bytes())?;
+        Write::flush(self)
+    }
+
+    fn set_raw_mode(&mut self, enable: bool) -> io::Result<()> {
+        match enable {
+            true => terminal::enable_raw_mode(),
+            false => terminal::disable_raw_mode(),
+        }
+    }
+
+    fn set_alternate_screen(&mut self, enable: bool) -> io::Result<()> {
+        match enable {
+            true => execute!(self, EnterAlternateScreen),
+            false => execute!(self, LeaveAlternateScreen),
+        }
+    }
+
+    fn set_mouse_capture(&mut self, enable: bool) -> io::Result<()> {
+        match enable {
+            true => execute!(self, EnableMouseCapture),
+            false => execute!(self, DisableMouseCapture),
+        }
+    }
+
+    fn set_bracketed_paste(&mut self, enable: bool) -> io::Result<()> {
+        match enable {
+            true => execute!(self, EnableBracketedPaste),
+            false => execute!(self, DisableBracketedPaste),
+        }
+    }
+
+    fn set_focus_change(&mut self, enable: bool) -> io::Result<()> {
+        match enable {
+            true => execute!(self, EnableFocusChange),
+            false => execute!(self, DisableFocusChange),
+        }
+    }
+
+    fn push_keyboard_enhancement(&mut self, flags: KeyboardEnhancementFlags) -> io::Result<()> {
+        let flags = ct::KeyboardEnhancementFlags::from_bits_truncate(flags.bits());
+        execute!(self, PushKeyboardEnhancementFlags(flags))
+    }
+
+    fn pop_keyboard_enhancement(&mut self) -> io::Result<()> {
+        execute!(self, PopKeyboardEnhancementFlags)
+    }
+
+    fn clear_below_cursor(&mut self) -> io::Result<()> {
+        execute!(self, Clear(ClearType::FromCursorDown))
+    }
+
+    /// crossterm sends the cursor position query to stdout, which may not
+    /// be where this backend draws, so send it through the backend and read
+    /// the reply from the tty
+    #[cfg(unix)]
+    fn cursor_position(&mut self) -> io::Result<(u16, u16)> {
+        let raw = terminal::is_raw_mode_enabled()?;
+        if !raw {
+            terminal::enable_raw_mode()?;
+        }
+        let position = self.write_sequence("\x1b[6n").and_then(|_| {
+            let reply = read_tty_reply(CURSOR_REPLY_TIMEOUT, |reply| {
+                parse_cursor_reply(reply).is_some()
+            })?;
+            Ok(parse_cursor_reply(&reply).unwrap_or_default())
+        });
+        if !raw {
+            terminal::disable_raw_mode()?;
+        }
+        position
+    }
+
+    fn restorer(&self, output: Output) -> Option<Restorer> {
+        Some(Box::new(move |enabled| {
+            enabled.undo(&mut CrosstermBackend::new(output.open()?))
+        }))
+    }
+}
+
+/// How long to wait for the terminal to answer a cursor position query
+#[cfg(unix)]
+const CURSOR_REPLY_TIMEOUT: Duration = Duration::from_millis(2000);
+
+/// Parse `ESC [ row ; col R` into a zero-based column and row. Anything
+/// before the reply, like keys typed in the meantime, is skipped.
+#[cfg(unix)]
+fn parse_cursor_reply(reply: &[u8]) -> Option<(u16, u16)> {
+    let end = reply.iter().rposition(|&b| b == b'R')?;
+    let start = reply[..end].iter().rposition(|&b| b == 0x1b)?;
+    let reply = std::str::from_utf8(&reply[start + 1..end]).ok()?;
+    let (row, col) = reply.strip_prefix('[')?.split_once(';')?;
+    let (row, col): (u16, u16) = (row.parse().ok()?, col.parse().ok()?);
+    Some((col.saturating_sub(1), row.saturating_sub(1)))
+}
+
+/// Reads events with crossterm's global event reader
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CrosstermInput;
+
+impl EventInput for CrosstermInput {
+    fn poll_event(&mut self, timeout: Duration) -> Result<Option<Event>, TermError> {
+        let poll_err = |e: io::Error| TermError::Poll(e.to_string());
+        match ct::poll(timeout).map_err(poll_err)? {
+            true => Ok(Some(ct::read().map_err(poll_err)?.into())),
+            false => Ok(None),
+        }
+    }
+}
+
+impl From<ct::Event> for Event {
+    fn from(event: ct::Event) -> Self {
+        match event {
+            ct::Event::Key(key) => Self::Key(key.into()),
+            ct::Event::Mouse(mouse) => Self::Mouse(mouse.into()),
+            ct::Event::Resize(width, height) => Self::Resize(width, height),
+            ct::Event::Paste(text) => Self::Paste(text),
+            ct::Event::FocusGained => Self::FocusGained,
+            ct::Event::FocusLost => Self::FocusLost,
+        }
+    }
+}
+
+impl From<ct::KeyEvent> for KeyEvent {
+    fn from(key: ct::KeyEvent) -> Self {
+        Self {
+            code: key.code.into(),
+            modifiers: key.modifiers.into(),
+            kind: match key.kind {
+                ct::KeyEventKind::Press => KeyEventKind::Press,
+                ct::KeyEventKind::Repeat => KeyEventKind::Repeat,
+                ct::KeyEventKind::Release => KeyEventKind::Release,
+            },
+        }
+    }
+}
+
+impl From<ct::KeyCode> for KeyCode {
+    fn from(code: ct::KeyCode) -> Self {
+        match code {
+            ct::KeyCode::Backspace => Self::Backspace,
+            ct::KeyCode::Enter => Self::Enter,
+            ct::KeyCode::Left => Self::Left,
+            ct::KeyCode::Right => Self::Right,
+            ct::KeyCode::Up => Self::Up,
+            ct::KeyCode::Down => Self::Down,
+            ct::KeyCode::Home => Self::Home,
+            ct::KeyCode::End => Self::End,
+            ct::KeyCode::PageUp => Self::PageUp,
+            ct::KeyCode::PageDown => Self::PageDown,
+            ct::KeyCode::Tab => Self::Tab,
+            ct::KeyCode::BackTab => Self::BackTab,
+            ct::KeyCode::Delete => Self::Delete,
+            ct::KeyCode::Insert => Self::Insert,
+            ct::KeyCode::F(n) => Self::F(n),
+            ct::KeyCode::Char(c) => Self::Char(c),
+            ct::KeyCode::Esc => Self::Esc,
+            ct::KeyCode::CapsLock => Self::CapsLock,
+            ct::KeyCode::ScrollLock => Self::ScrollLock,
+            ct::KeyCode::NumLock => Self::NumLock,
+            ct::KeyCode::PrintScreen => Self::PrintScreen,
+            ct::KeyCode::Pause => Self::Pause,
+            ct::KeyCode::Menu => Self::Menu,
+            ct::KeyCode::KeypadBegin => Self::KeypadBegin,
+            ct::KeyCode::Null | ct::KeyCode::Media(_) | ct::KeyCode::Modifier(_) => Self::Null,
+        }
+    }
+}
+
+impl From<ct::KeyModifiers> for KeyModifiers {
+    fn from(modifiers: ct::KeyModifiers) -> Self {
+        // the bits are the same
+        Self::from_bits_truncate(modifiers.bits())
+    }
+}
+
+impl From<ct::MouseEvent> for MouseEvent {
+    fn from(mouse: ct::MouseEvent) -> Self {
+        let button = |button| match button {
+            ct::MouseButton::Left => MouseButton::Left,
+            ct::MouseButton::Right => MouseButton::Right,
+            ct::MouseButton::Middle => MouseButton::Middle,
+        };
+        Self {
+            kind: match mouse.kind {
+                ct::MouseEventKind::Down(b) => MouseEventKind::Down(button(b)),
+                ct::MouseEventKind::Up(b) => MouseEventKind::Up(button(b)),
+                ct::MouseEventKind::Drag(b) => MouseEventKind::Drag(button(b)),
+                ct::MouseEventKind::Moved => MouseEventKind::Moved,
+                ct::MouseEventKind::ScrollDown => MouseEventKind::ScrollDown,
+                ct::MouseEventKind::ScrollUp => MouseEventKind::ScrollUp,
+            },
+            column: mouse.column,
+            row: mouse.row,
+            modifiers: mouse.modifiers.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::crossterm::event as ct;
+
+    use crate::{
+        event::{Event, MouseButton, MouseEventKind},
+        keys::{KeyCode, KeyEvent, KeyModifiers},
+    };
+
+    #[test]
+    fn key_events_convert() {
+        let key = ct::KeyEvent::new(ct::KeyCode::Char('x'), ct::KeyModifiers::CONTROL);
+        assert_eq!(
+            Event::from(ct::Event::Key(key)),
+            Event::Key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL))
+        );
+    }
+
+    #[test]
+    fn mouse_events_convert() {
+        let mouse = ct::MouseEvent {
+            kind: ct::MouseEventKind::Drag(ct::MouseButton::Right),
+            column: 3,
+            row: 4,
+            modifiers: ct::KeyModifiers::ALT,
+        };
+        let Event::Mouse(mouse) = ct::Event::Mouse(mouse).into() else {
+            panic!("not a mouse event");
+        };
+        assert_eq!(mouse.kind, MouseEventKind::Drag(MouseButton::Right));
+        assert_eq!((mouse.column, mouse.row), (3, 4));
+        assert_eq!(mouse.modifiers, KeyModifiers::ALT);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cursor_replies_parse() {
+        assert_eq!(super::parse_cursor_reply(b"\x1b[12;40R"), Some((39, 11)));
+        assert_eq!(super::parse_cursor_reply(b"j\x1b[1;1R"), Some((0, 0)));
+        assert_eq!(super::parse_cursor_reply(b"\x1b[12;4"), None);
+    }
+}
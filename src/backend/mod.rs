@@ -0,0 +1,205 @@
+//! Terminal libraries the terminal setup and event reading run on. Each one
+//! is behind a cargo feature of the same name, `crossterm` is enabled by
+//! default. Events of every backend are converted to the types in `event`
+//! and `keys`, so components and keymaps do not depend on the backend.
+
+#[cfg(feature = "crossterm")]
+pub mod crossterm;
+#[cfg(feature = "termion")]
+pub mod termion;
+#[cfg(feature = "termwiz")]
+pub mod termwiz;
+
+use std::{io, time::Duration};
+
+use ratatui::backend::Backend;
+
+use crate::term::ByteCounter;
+#[cfg(any(feature = "crossterm", feature = "termion"))]
+use crate::term::{CountingWriter, OutputWriter};
+use crate::{
+    event::Event,
+    term::{InitOptions, KeyboardEnhancementFlags, Output, TermError},
+};
+
+#[cfg(not(any(feature = "crossterm", feature = "termion", feature = "termwiz")))]
+compile_error!("one of the `crossterm`, `termion` or `termwiz` features has to be enabled");
+
+/// Backend used by `term::init` and `InitOptions::init`. This is crossterm
+/// if its feature is enabled, then termion, then termwiz.
+#[cfg(feature = "crossterm")]
+pub type DefaultBackend = ratatui::backend::CrosstermBackend<CountingWriter<OutputWriter>>;
+#[cfg(all(feature = "termion", not(feature = "crossterm")))]
+pub type DefaultBackend = ratatui::backend::TermionBackend<CountingWriter<OutputWriter>>;
+#[cfg(all(
+    feature = "termwiz",
+    not(any(feature = "crossterm", feature = "termion"))
+))]
+pub type DefaultBackend = self::termwiz::TermwizBackend;
+
+/// Input of the default backend, used by `EventSource::new` and `EventReader::new`
+pub type DefaultInput = <DefaultBackend as TermBackend>::Input;
+
+/// Create the default backend drawing to `output`, together with the count
+/// of bytes written to it if the backend allows counting them
+#[cfg(any(feature = "crossterm", feature = "termion"))]
+pub(crate) fn default_backend(output: Output) -> io::Result<(DefaultBackend, Option<ByteCounter>)> {
+    let writer = CountingWriter::new(output.open()?);
+    let counter = writer.counter();
+    Ok((DefaultBackend::new(writer), Some(counter)))
+}
+
+/// Create the default backend drawing to `output`, together with the count
+/// of bytes written to it if the backend allows counting them
+#[cfg(not(any(feature = "crossterm", feature = "termion")))]
+pub(crate) fn default_backend(output: Output) -> io::Result<(DefaultBackend, Option<ByteCounter>)> {
+    Ok((DefaultBackend::with_output(output)?, None))
+}
+
+/// Read the answer to a query written to the terminal from the tty until
+/// `done` accepts it. Fails with `TimedOut` if the terminal does not
+/// answer within `timeout`.
+#[cfg(unix)]
+pub(crate) fn read_tty_reply(
+    timeout: Duration,
+    done: impl Fn(&[u8]) -> bool,
+) -> io::Result<Vec<u8>> {
+    use std::{io::Read, os::fd::AsRawFd, time::Instant};
+
+    let deadline = Instant::now() + timeout;
+    let mut tty = std::fs::File::open("/dev/tty")?;
+    let mut reply = Vec::new();
+    while !done(&reply) {
+        let wait = deadline.saturating_duration_since(Instant::now());
+        let mut fd = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: `fd` is a single valid pollfd for the duration of the call
+        match unsafe { libc::poll(&mut fd, 1, wait.as_millis() as libc::c_int) } {
+            0 => {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "the terminal did not answer in time",
+                ))
+            }
+            n if n < 0 => match io::Error::last_os_error() {
+                e if e.kind() == io::ErrorKind::Interrupted => continue,
+                e => return Err(e),
+            },
+            _ => {}
+        }
+        let mut buf = [0; 64];
+        let read = tty.read(&mut buf)?;
+        reply.extend_from_slice(&buf[..read]);
+    }
+    Ok(reply)
+}
+
+/// Called from the panic hook with what was enabled on the terminal to undo it
+pub type Restorer = Box<dyn FnMut(&InitOptions) -> io::Result<()> + Send>;
+
+/// Source of terminal input. Closures taking the timeout implement this
+/// too, which is handy for feeding scripted events in tests.
+pub trait EventInput {
+    /// Wait up to `timeout` for the next event. Returns `None` if nothing
+    /// arrived in time.
+    fn poll_event(&mut self, timeout: Duration) -> Result<Option<Event>, TermError>;
+}
+
+impl<F> EventInput for F
+where
+    F: FnMut(Duration) -> Result<Option<Event>, TermError>,
+{
+    fn poll_event(&mut self, timeout: Duration) -> Result<Option<Event>, TermError> {
+        self(timeout)
+    }
+}
+
+/// The parts of setting up and restoring the terminal which differ between
+/// terminal libraries. Modes without a dedicated method in the library are
+/// switched with the standard escape sequences written by `write_sequence`.
+pub trait TermBackend: Backend {
+    /// Reads input from the terminal this backend draws on
+    type Input: EventInput + Default + Send + 'static;
+
+    /// Write an escape sequence to the terminal and flush it
+    fn write_sequence(&mut self, sequence: &str) -> io::Result<()>;
+
+    fn set_raw_mode(&mut self, enable: bool) -> io::Result<()>;
+
+    fn set_alternate_screen(&mut self, enable: bool) -> io::Result<()> {
+        self.write_sequence(if enable { "\x1b[?1049h" } else { "\x1b[?1049l" })
+    }
+
+    /// Report presses, releases, drags and movement with SGR coordinates
+    fn set_mouse_capture(&mut self, enable: bool) -> io::Result<()> {
+        self.write_sequence(if enable {
+            "\x1b[?1000h\x1b[?1002h\x1b[?1003h\x1b[?1015h\x1b[?1006h"
+        } else {
+            "\x1b[?1006l\x1b[?1015l\x1b[?1003l\x1b[?1002l\x1b[?1000l"
+        })
+    }
+
+    fn set_bracketed_paste(&mut self, enable: bool) -> io::Result<()> {
+        self.write_sequence(if enable { "\x1b[?2004h" } else { "\x1b[?2004l" })
+    }
+
+    fn set_focus_change(&mut self, enable: bool) -> io::Result<()> {
+        self.write_sequence(if enable { "\x1b[?1004h" } else { "\x1b[?1004l" })
+    }
+
+    /// Enable the kitty keyboard protocol with `flags`
+    fn push_keyboard_enhancement(&mut self, flags: KeyboardEnhancementFlags) -> io::Result<()> {
+        self.write_sequence(&format!("\x1b[>{}u", flags.bits()))
+    }
+
+    fn pop_keyboard_enhancement(&mut self) -> io::Result<()> {
+        self.write_sequence("\x1b[<1u")
+    }
+
+    /// Clear from the cursor to the end of the screen
+    fn clear_below_cursor(&mut self) -> io::Result<()> {
+        self.write_sequence("\x1b[J")
+    }
+
+    /// Query the position of the cursor on the screen, which inline
+    /// viewports start from
+    fn cursor_position(&mut self) -> io::Result<(u16, u16)> {
+        self.get_cursor()
+    }
+
+    /// Something that can restore the terminal from the panic hook, where
+    /// the guard owning this backend is out of reach. Backends writing to a
+    /// stream assume it is `output`.
+    fn restorer(&self, _output: Output) -> Option<Restorer> {
+        None
+    }
+}
+
+/// Input which never delivers anything, for backends without a terminal
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct NoInput;
+
+#[cfg(test)]
+impl EventInput for NoInput {
+    fn poll_event(&mut self, _timeout: Duration) -> Result<Option<Event>, TermError> {
+        Ok(None)
+    }
+}
+
+/// Lets unit tests put a `TestBackend` behind a `TerminalGuard`
+#[cfg(test)]
+impl TermBackend for ratatui::backend::TestBackend {
+    type Input = NoInput;
+
+    fn write_sequence(&mut self, _sequence: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_raw_mode(&mut self, _enable: bool) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,285 @@
+//! Terminal setup and input through termion, which only works on unix
+
+use std::{
+    fs::File,
+    io::{self, Write},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Mutex, MutexGuard, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use ::termion::{
+    event as tm,
+    input::TermRead,
+    raw::{IntoRawMode, RawTerminal},
+};
+use ratatui::backend::TermionBackend;
+
+use super::{EventInput, Restorer, TermBackend};
+use crate::{
+    event::{Event, MouseButton, MouseEvent, MouseEventKind},
+    keys::{KeyCode, KeyEvent, KeyModifiers},
+    term::{Output, TermError},
+};
+
+/// How often `TermionInput` checks for a new terminal size while waiting,
+/// since termion does not report resizes
+const RESIZE_POLL: Duration = Duration::from_millis(50);
+
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+/// How long a paste may stall before what arrived of it is returned, so a
+/// lost end marker does not block input for good
+const PASTE_TIMEOUT: Duration = Duration::from_millis(2000);
+
+/// The tty while it is in raw mode. Dropping it leaves raw mode, keeping
+/// it here instead of in the backend lets the panic hook do that too.
+fn raw_tty() -> MutexGuard<'static, Option<RawTerminal<File>>> {
+    static RAW: Mutex<Option<RawTerminal<File>>> = Mutex::new(None);
+    RAW.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl<W: Write> TermBackend for TermionBackend<W> {
+    type Input = TermionInput;
+
+    fn write_sequence(&mut self, sequence: &str) -> io::Result<()> {
+        self.write_all(sequence.as_bytes())?;
+        Write::flush(self)
+    }
+
+    fn set_raw_mode(&mut self, enable: bool) -> io::Result<()> {
+        let mut raw = raw_tty();
+        match enable {
+            true if raw.is_none() => *raw = Some(::termion::get_tty()?.into_raw_mode()?),
+            true => {}
+            false => drop(raw.take()),
+        }
+        Ok(())
+    }
+
+    fn restorer(&self, output: Output) -> Option<Restorer> {
+        Some(Box::new(move |enabled| {
+            enabled.undo(&mut TermionBackend::new(output.open()?))
+        }))
+    }
+}
+
+type Delivery = io::Result<tm::Event>;
+
+/// Events read from the tty by a thread that lives as long as the process,
+/// since a blocking read cannot be interrupted. It is shared by every
+/// `TermionInput`.
+fn tty_events() -> MutexGuard<'static, Receiver<Delivery>> {
+    static EVENTS: OnceLock<Mutex<Receiver<Delivery>>> = OnceLock::new();
+    let events = EVENTS.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let tty = match ::termion::get_tty() {
+                Ok(tty) => tty,
+                Err(e) => {
+                    let _ = sender.send(Err(e));
+                    return;
+                }
+            };
+            for event in tty.events() {
+                if sender.send(event).is_err() {
+                    return;
+                }
+            }
+        });
+        Mutex::new(receiver)
+    });
+    events.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Reads events from the tty. Resizes are noticed by watching the
+/// terminal size, pastes and focus changes are parsed from the sequences
+/// termion does not know about.
+#[derive(Debug, Default)]
+pub struct TermionInput {
+    size: Option<(u16, u16)>,
+    /// Button of the last press, termion does not report it on release
+    button: Option<MouseButton>,
+}
+
+impl TermionInput {
+    fn resized(&mut self) -> Option<Event> {
+        let size = ::termion::terminal_size().ok()?;
+        match self.size.replace(size) {
+            Some(previous) if previous != size => Some(Event::Resize(size.0, size.1)),
+            _ => None,
+        }
+    }
+
+    fn convert(&mut self, event: tm::Event, events: &Receiver<Delivery>) -> Option<Event> {
+        match event {
+            tm::Event::Key(key) => Some(Event::Key(convert_key(key))),
+            tm::Event::Mouse(mouse) => self.convert_mouse(mouse).map(Event::Mouse),
+            tm::Event::Unsupported(bytes) => match bytes.as_slice() {
+                b"\x1b[I" => Some(Event::FocusGained),
+                b"\x1b[O" => Some(Event::FocusLost),
+                PASTE_START => Some(Event::Paste(read_paste(events, PASTE_TIMEOUT))),
+                _ => None,
+            },
+        }
+    }
+
+    fn convert_mouse(&mut self, mouse: tm::MouseEvent) -> Option<MouseEvent> {
+        let (kind, x, y) = match mouse {
+            tm::MouseEvent::Press(button, x, y) => {
+                let kind = match button {
+                    tm::MouseButton::Left => MouseEventKind::Down(MouseButton::Left),
+                    tm::MouseButton::Right => MouseEventKind::Down(MouseButton::Right),
+                    tm::MouseButton::Middle => MouseEventKind::Down(MouseButton::Middle),
+                    tm::MouseButton::WheelUp => MouseEventKind::ScrollUp,
+                    tm::MouseButton::WheelDown => MouseEventKind::ScrollDown,
+                };
+                if let MouseEventKind::Down(button) = kind {
+                    self.button = Some(button);
+                }
+                (kind, x, y)
+            }
+            tm::MouseEvent::Release(x, y) => (MouseEventKind::Up(self.button.take()?), x, y),
+            tm::MouseEvent::Hold(x, y) => (MouseEventKind::Drag(self.button?), x, y),
+        };
+        Some(MouseEvent {
+            kind,
+            // termion reports one-based coordinates
+            column: x.saturating_sub(1),
+            row: y.saturating_sub(1),
+            modifiers: KeyModifiers::NONE,
+        })
+    }
+}
+
+impl EventInput for TermionInput {
+    fn poll_event(&mut self, timeout: Duration) -> Result<Option<Event>, TermError> {
+        let deadline = Instant::now() + timeout;
+        let events = tty_events();
+        loop {
+            if let Some(resize) = self.resized() {
+                return Ok(Some(resize));
+            }
+            let wait = deadline
+                .saturating_duration_since(Instant::now())
+                .min(RESIZE_POLL);
+            match events.recv_timeout(wait) {
+                Ok(Ok(event)) => {
+                    if let Some(event) = self.convert(event, &events) {
+                        return Ok(Some(event));
+                    }
+                }
+                Ok(Err(e)) => return Err(TermError::Poll(e.to_string())),
+                Err(RecvTimeoutError::Timeout) if Instant::now() >= deadline => return Ok(None),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(TermError::Poll(String::from("the tty was closed")))
+                }
+            }
+        }
+    }
+}
+
+/// Collect the keys of a bracketed paste up to its end marker. If no key
+/// arrives for `timeout`, the text collected so far is returned.
+fn read_paste(events: &Receiver<Delivery>, timeout: Duration) -> String {
+    let mut text = String::new();
+    while let Ok(Ok(event)) = events.recv_timeout(timeout) {
+        match event {
+            tm::Event::Unsupported(bytes) if bytes == PASTE_END => break,
+            tm::Event::Key(tm::Key::Char(c)) => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+fn convert_key(key: tm::Key) -> KeyEvent {
+    let (code, modifiers) = match key {
+        tm::Key::Backspace => (KeyCode::Backspace, KeyModifiers::NONE),
+        tm::Key::Left => (KeyCode::Left, KeyModifiers::NONE),
+        tm::Key::Right => (KeyCode::Right, KeyModifiers::NONE),
+        tm::Key::Up => (KeyCode::Up, KeyModifiers::NONE),
+        tm::Key::Down => (KeyCode::Down, KeyModifiers::NONE),
+        tm::Key::Home => (KeyCode::Home, KeyModifiers::NONE),
+        tm::Key::End => (KeyCode::End, KeyModifiers::NONE),
+        tm::Key::PageUp => (KeyCode::PageUp, KeyModifiers::NONE),
+        tm::Key::PageDown => (KeyCode::PageDown, KeyModifiers::NONE),
+        tm::Key::BackTab => (KeyCode::BackTab, KeyModifiers::SHIFT),
+        tm::Key::Delete => (KeyCode::Delete, KeyModifiers::NONE),
+        tm::Key::Insert => (KeyCode::Insert, KeyModifiers::NONE),
+        tm::Key::F(n) => (KeyCode::F(n), KeyModifiers::NONE),
+        tm::Key::Char('\n') => (KeyCode::Enter, KeyModifiers::NONE),
+        tm::Key::Char('\t') => (KeyCode::Tab, KeyModifiers::NONE),
+        // crossterm reports shift with upper case letters, do the same
+        tm::Key::Char(c) if c.is_uppercase() => (KeyCode::Char(c), KeyModifiers::SHIFT),
+        tm::Key::Char(c) => (KeyCode::Char(c), KeyModifiers::NONE),
+        tm::Key::Alt(c) => (KeyCode::Char(c), KeyModifiers::ALT),
+        tm::Key::Ctrl(c) => (KeyCode::Char(c), KeyModifiers::CONTROL),
+        tm::Key::Esc => (KeyCode::Esc, KeyModifiers::NONE),
+        _ => (KeyCode::Null, KeyModifiers::NONE),
+    };
+    KeyEvent::new(code, modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::mpsc, time::Duration};
+
+    use ::termion::event as tm;
+
+    use super::{convert_key, read_paste, TermionInput};
+    use crate::{
+        event::{Event, MouseButton, MouseEventKind},
+        keys::{KeyCode, KeyEvent, KeyModifiers},
+    };
+
+    #[test]
+    fn keys_convert() {
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(convert_key(tm::Key::Ctrl('c')), ctrl_c);
+        assert_eq!(
+            convert_key(tm::Key::Char('\n')),
+            KeyEvent::from(KeyCode::Enter)
+        );
+    }
+
+    #[test]
+    fn release_reports_pressed_button() {
+        let mut input = TermionInput::default();
+        let press = tm::MouseEvent::Press(tm::MouseButton::Right, 5, 2);
+        let down = input.convert_mouse(press).unwrap();
+        assert_eq!(down.kind, MouseEventKind::Down(MouseButton::Right));
+        assert_eq!((down.column, down.row), (4, 1));
+
+        let up = input.convert_mouse(tm::MouseEvent::Release(5, 2)).unwrap();
+        assert_eq!(up.kind, MouseEventKind::Up(MouseButton::Right));
+    }
+
+    #[test]
+    fn bracketed_paste_is_collected() {
+        let (sender, events) = mpsc::channel();
+        for c in "hi".chars() {
+            sender.send(Ok(tm::Event::Key(tm::Key::Char(c)))).unwrap();
+        }
+        sender
+            .send(Ok(tm::Event::Unsupported(b"\x1b[201~".to_vec())))
+            .unwrap();
+
+        let start = tm::Event::Unsupported(b"\x1b[200~".to_vec());
+        let pasted = TermionInput::default().convert(start, &events);
+        assert_eq!(pasted, Some(Event::Paste(String::from("hi"))));
+    }
+
+    #[test]
+    fn unterminated_paste_times_out() {
+        let (sender, events) = mpsc::channel();
+        sender.send(Ok(tm::Event::Key(tm::Key::Char('h')))).unwrap();
+        // the end marker never arrives, but the sender stays alive
+        assert_eq!(read_paste(&events, Duration::from_millis(10)), "h");
+        drop(sender);
+    }
+}
@@ -0,0 +1,477 @@
+//! Drawing, terminal setup and input through termwiz. Ratatui has no
+//! termwiz backend of its own, so `TermwizBackend` implements one.
+
+#[cfg(unix)]
+use std::fs::OpenOptions;
+use std::{
+    io,
+    sync::{Arc, Mutex, MutexGuard, Weak},
+    thread,
+    time::{Duration, Instant},
+};
+
+use ::termwiz::{
+    caps::{Capabilities, ProbeHints},
+    cell::{AttributeChange, Blink, Intensity, Underline},
+    color::{ColorAttribute, RgbColor},
+    input::{self as tw, InputEvent, Modifiers, MouseButtons},
+    surface::{Change, CursorVisibility, Position},
+    terminal::{buffered::BufferedTerminal, SystemTerminal, Terminal},
+};
+use ratatui::{
+    backend::Backend,
+    buffer::Cell,
+    layout::Rect,
+    style::{Color, Modifier},
+};
+
+use super::{EventInput, Restorer, TermBackend};
+use crate::{
+    event::{Event, MouseButton, MouseEvent, MouseEventKind},
+    keys::{KeyCode, KeyEvent, KeyModifiers},
+    term::{Output, TermError},
+};
+
+type Shared = Arc<Mutex<BufferedTerminal<SystemTerminal>>>;
+
+/// How long `TermwizInput` holds on to the terminal while waiting for
+/// input, so drawing from another thread is not blocked for long
+const INPUT_POLL: Duration = Duration::from_millis(10);
+
+/// The terminal of the most recently created `TermwizBackend`, which
+/// `TermwizInput::default` reads from
+fn current() -> MutexGuard<'static, Weak<Mutex<BufferedTerminal<SystemTerminal>>>> {
+    static CURRENT: Mutex<Weak<Mutex<BufferedTerminal<SystemTerminal>>>> = Mutex::new(Weak::new());
+    CURRENT.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn io_err(e: ::termwiz::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+#[cfg(unix)]
+fn open(caps: Capabilities, output: Output) -> io::Result<SystemTerminal> {
+    let tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+    match output {
+        Output::Stdout => SystemTerminal::new_with(caps, &tty, &io::stdout()),
+        Output::Stderr => SystemTerminal::new_with(caps, &tty, &io::stderr()),
+        Output::Tty => SystemTerminal::new_with(caps, &tty, &tty),
+    }
+    .map_err(io_err)
+}
+
+#[cfg(not(unix))]
+fn open(caps: Capabilities, _output: Output) -> io::Result<SystemTerminal> {
+    SystemTerminal::new(caps).map_err(io_err)
+}
+
+/// Draws on a terminal through termwiz. The terminal is
+/// shared with the `TermwizInput` reading from it. Inline viewports are
+/// not supported since termwiz cannot query the cursor position.
+pub struct TermwizBackend {
+    terminal: Shared,
+}
+
+impl TermwizBackend {
+    /// Open the controlling terminal. Mouse capture and bracketed paste
+    /// are left to `InitOptions` instead of termwiz's raw mode.
+    pub fn new() -> io::Result<Self> {
+        Self::with_output(Output::Tty)
+    }
+
+    /// Draw to `output`, which has to be a terminal. Input is always read
+    /// from the controlling terminal. On Windows this is the console.
+    pub fn with_output(output: Output) -> io::Result<Self> {
+        let hints = ProbeHints::new_from_env()
+            .mouse_reporting(Some(false))
+            .bracketed_paste(Some(false));
+        let caps = Capabilities::new_with_hints(hints).map_err(io_err)?;
+        let terminal = open(caps, output)?;
+        let terminal = Arc::new(Mutex::new(BufferedTerminal::new(terminal).map_err(io_err)?));
+        *current() = Arc::downgrade(&terminal);
+        Ok(Self { terminal })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BufferedTerminal<SystemTerminal>> {
+        self.terminal.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Backend for TermwizBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        let mut terminal = self.lock();
+        for (x, y, cell) in content {
+            terminal.add_changes(vec![
+                Change::CursorPosition {
+                    x: Position::Absolute(x as usize),
+                    y: Position::Absolute(y as usize),
+                },
+                AttributeChange::Foreground(color(cell.fg)).into(),
+                AttributeChange::Background(color(cell.bg)).into(),
+            ]);
+            terminal.add_changes(attributes(cell.modifier));
+            terminal.add_change(cell.symbol.as_str());
+        }
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.lock()
+            .add_change(Change::CursorVisibility(CursorVisibility::Hidden));
+        Backend::flush(self)
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.lock()
+            .add_change(Change::CursorVisibility(CursorVisibility::Visible));
+        Backend::flush(self)
+    }
+
+    fn get_cursor(&mut self) -> io::Result<(u16, u16)> {
+        let (x, y) = self.lock().cursor_position();
+        Ok((x as u16, y as u16))
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
+        self.lock().add_change(Change::CursorPosition {
+            x: Position::Absolute(x as usize),
+            y: Position::Absolute(y as usize),
+        });
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.lock()
+            .add_change(Change::ClearScreen(ColorAttribute::Default));
+        Backend::flush(self)
+    }
+
+    fn size(&self) -> io::Result<Rect> {
+        let mut terminal = self.lock();
+        terminal.check_for_resize().map_err(io_err)?;
+        let (width, height) = terminal.dimensions();
+        Ok(Rect::new(0, 0, width as u16, height as u16))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock().flush().map_err(io_err)
+    }
+}
+
+impl TermBackend for TermwizBackend {
+    type Input = TermwizInput;
+
+    fn write_sequence(&mut self, sequence: &str) -> io::Result<()> {
+        let mut buffered = self.lock();
+        let terminal = buffered.terminal();
+        terminal
+            .render(&[Change::Text(sequence.to_string())])
+            .map_err(io_err)?;
+        terminal.flush().map_err(io_err)
+    }
+
+    fn set_raw_mode(&mut self, enable: bool) -> io::Result<()> {
+        let mut buffered = self.lock();
+        match enable {
+            true => buffered.terminal().set_raw_mode(),
+            false => buffered.terminal().set_cooked_mode(),
+        }
+        .map_err(io_err)
+    }
+
+    fn set_alternate_screen(&mut self, enable: bool) -> io::Result<()> {
+        let mut buffered = self.lock();
+        match enable {
+            true => buffered.terminal().enter_alternate_screen(),
+            false => buffered.terminal().exit_alternate_screen(),
+        }
+        .map_err(io_err)
+    }
+
+    fn cursor_position(&mut self) -> io::Result<(u16, u16)> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "termwiz cannot query the cursor position for an inline viewport",
+        ))
+    }
+
+    fn restorer(&self, _output: Output) -> Option<Restorer> {
+        let terminal = Arc::downgrade(&self.terminal);
+        Some(Box::new(move |enabled| {
+            // the terminal is gone or in use by the panicking thread
+            let Some(terminal) = terminal.upgrade() else {
+                return Ok(());
+            };
+            if terminal.try_lock().is_err() {
+                return Ok(());
+            }
+            enabled.undo(&mut TermwizBackend { terminal })
+        }))
+    }
+}
+
+fn color(color: Color) -> ColorAttribute {
+    let index = match color {
+        Color::Reset => return ColorAttribute::Default,
+        Color::Rgb(r, g, b) => {
+            return ColorAttribute::TrueColorWithDefaultFallback(RgbColor::new_8bpc(r, g, b).into())
+        }
+        Color::Indexed(i) => i,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+    };
+    ColorAttribute::PaletteIndex(index)
+}
+
+fn attributes(modifier: Modifier) -> Vec<Change> {
+    let intensity = if modifier.contains(Modifier::BOLD) {
+        Intensity::Bold
+    } else if modifier.contains(Modifier::DIM) {
+        Intensity::Half
+    } else {
+        Intensity::Normal
+    };
+    let underline = match modifier.contains(Modifier::UNDERLINED) {
+        true => Underline::Single,
+        false => Underline::None,
+    };
+    let blink = if modifier.contains(Modifier::RAPID_BLINK) {
+        Blink::Rapid
+    } else if modifier.contains(Modifier::SLOW_BLINK) {
+        Blink::Slow
+    } else {
+        Blink::None
+    };
+    [
+        AttributeChange::Intensity(intensity),
+        AttributeChange::Underline(underline),
+        AttributeChange::Blink(blink),
+        AttributeChange::Italic(modifier.contains(Modifier::ITALIC)),
+        AttributeChange::Reverse(modifier.contains(Modifier::REVERSED)),
+        AttributeChange::Invisible(modifier.contains(Modifier::HIDDEN)),
+        AttributeChange::StrikeThrough(modifier.contains(Modifier::CROSSED_OUT)),
+    ]
+    .into_iter()
+    .map(Change::Attribute)
+    .collect()
+}
+
+/// Reads input from the terminal of a `TermwizBackend`. The default
+/// instance reads from the most recently created backend.
+#[derive(Debug)]
+pub struct TermwizInput {
+    terminal: Weak<Mutex<BufferedTerminal<SystemTerminal>>>,
+    /// Buttons held during the previous mouse event, termwiz only reports
+    /// which buttons are held
+    buttons: MouseButtons,
+}
+
+impl Default for TermwizInput {
+    fn default() -> Self {
+        Self {
+            terminal: current().clone(),
+            buttons: MouseButtons::NONE,
+        }
+    }
+}
+
+impl EventInput for TermwizInput {
+    fn poll_event(&mut self, timeout: Duration) -> Result<Option<Event>, TermError> {
+        let terminal = self
+            .terminal
+            .upgrade()
+            .ok_or_else(|| TermError::Poll(String::from("the termwiz terminal was dropped")))?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let wait = deadline
+                .saturating_duration_since(Instant::now())
+                .min(INPUT_POLL);
+            let input = {
+                let mut buffered = terminal.lock().unwrap_or_else(|e| e.into_inner());
+                buffered.terminal().poll_input(Some(wait))
+            };
+            match input.map_err(|e| TermError::Poll(e.to_string()))? {
+                Some(input) => {
+                    if let Some(event) = self.convert(input) {
+                        return Ok(Some(event));
+                    }
+                }
+                None if Instant::now() >= deadline => return Ok(None),
+                // give drawing a chance to take the lock
+                None => thread::yield_now(),
+            }
+        }
+    }
+}
+
+impl TermwizInput {
+    fn convert(&mut self, input: InputEvent) -> Option<Event> {
+        match input {
+            InputEvent::Key(key) => Some(Event::Key(convert_key(key))),
+            InputEvent::Mouse(mouse) => Some(Event::Mouse(self.convert_mouse(mouse))),
+            InputEvent::Resized { cols, rows } => Some(Event::Resize(cols as u16, rows as u16)),
+            InputEvent::Paste(text) => Some(Event::Paste(text)),
+            InputEvent::PixelMouse(_) | InputEvent::Wake => None,
+        }
+    }
+
+    fn convert_mouse(&mut self, mouse: tw::MouseEvent) -> MouseEvent {
+        let held = mouse.mouse_buttons;
+        let kind = if held.contains(MouseButtons::VERT_WHEEL) {
+            match held.contains(MouseButtons::WHEEL_POSITIVE) {
+                true => MouseEventKind::ScrollUp,
+                false => MouseEventKind::ScrollDown,
+            }
+        } else {
+            let previous = std::mem::replace(&mut self.buttons, held.clone());
+            let pressed = held.clone() - previous.clone();
+            let released = previous - held.clone();
+            match (button(pressed), button(released), button(held)) {
+                (Some(b), _, _) => MouseEventKind::Down(b),
+                (_, Some(b), _) => MouseEventKind::Up(b),
+                (_, _, Some(b)) => MouseEventKind::Drag(b),
+                _ => MouseEventKind::Moved,
+            }
+        };
+        // SGR reports on unix are one-based
+        let origin = if cfg!(unix) { 1 } else { 0 };
+        MouseEvent {
+            kind,
+            column: mouse.x.saturating_sub(origin),
+            row: mouse.y.saturating_sub(origin),
+            modifiers: convert_modifiers(mouse.modifiers),
+        }
+    }
+}
+
+fn button(buttons: MouseButtons) -> Option<MouseButton> {
+    if buttons.contains(MouseButtons::LEFT) {
+        Some(MouseButton::Left)
+    } else if buttons.contains(MouseButtons::RIGHT) {
+        Some(MouseButton::Right)
+    } else if buttons.contains(MouseButtons::MIDDLE) {
+        Some(MouseButton::Middle)
+    } else {
+        None
+    }
+}
+
+fn convert_modifiers(modifiers: Modifiers) -> KeyModifiers {
+    let mut out = KeyModifiers::NONE;
+    let pairs = [
+        (Modifiers::SHIFT, KeyModifiers::SHIFT),
+        (Modifiers::CTRL, KeyModifiers::CONTROL),
+        (Modifiers::ALT, KeyModifiers::ALT),
+        (Modifiers::SUPER, KeyModifiers::SUPER),
+    ];
+    for (tw, neutral) in pairs {
+        if modifiers.intersects(tw) {
+            out |= neutral;
+        }
+    }
+    out
+}
+
+fn convert_key(key: tw::KeyEvent) -> KeyEvent {
+    let modifiers = convert_modifiers(key.modifiers);
+    let code = match key.key {
+        tw::KeyCode::Char(c) => KeyCode::Char(c),
+        tw::KeyCode::Backspace => KeyCode::Backspace,
+        tw::KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+        tw::KeyCode::Tab => KeyCode::Tab,
+        tw::KeyCode::Enter => KeyCode::Enter,
+        tw::KeyCode::Escape => KeyCode::Esc,
+        tw::KeyCode::PageUp | tw::KeyCode::KeyPadPageUp => KeyCode::PageUp,
+        tw::KeyCode::PageDown | tw::KeyCode::KeyPadPageDown => KeyCode::PageDown,
+        tw::KeyCode::End | tw::KeyCode::KeyPadEnd => KeyCode::End,
+        tw::KeyCode::Home | tw::KeyCode::KeyPadHome => KeyCode::Home,
+        tw::KeyCode::LeftArrow | tw::KeyCode::ApplicationLeftArrow => KeyCode::Left,
+        tw::KeyCode::RightArrow | tw::KeyCode::ApplicationRightArrow => KeyCode::Right,
+        tw::KeyCode::UpArrow | tw::KeyCode::ApplicationUpArrow => KeyCode::Up,
+        tw::KeyCode::DownArrow | tw::KeyCode::ApplicationDownArrow => KeyCode::Down,
+        tw::KeyCode::Insert => KeyCode::Insert,
+        tw::KeyCode::Delete => KeyCode::Delete,
+        tw::KeyCode::Function(n) => KeyCode::F(n),
+        tw::KeyCode::CapsLock => KeyCode::CapsLock,
+        tw::KeyCode::ScrollLock => KeyCode::ScrollLock,
+        tw::KeyCode::NumLock => KeyCode::NumLock,
+        tw::KeyCode::PrintScreen => KeyCode::PrintScreen,
+        tw::KeyCode::Pause => KeyCode::Pause,
+        tw::KeyCode::Menu => KeyCode::Menu,
+        tw::KeyCode::KeyPadBegin => KeyCode::KeypadBegin,
+        _ => KeyCode::Null,
+    };
+    KeyEvent::new(code, modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Weak;
+
+    use ::termwiz::input::{self as tw, Modifiers, MouseButtons};
+
+    use super::{convert_key, TermwizInput};
+    use crate::{
+        event::{MouseButton, MouseEventKind},
+        keys::{KeyCode, KeyEvent, KeyModifiers},
+    };
+
+    fn mouse(input: &mut TermwizInput, buttons: MouseButtons) -> MouseEventKind {
+        let event = tw::MouseEvent {
+            x: 1,
+            y: 1,
+            mouse_buttons: buttons,
+            modifiers: Modifiers::NONE,
+        };
+        input.convert_mouse(event).kind
+    }
+
+    #[test]
+    fn shift_tab_is_back_tab() {
+        let key = tw::KeyEvent {
+            key: tw::KeyCode::Tab,
+            modifiers: Modifiers::SHIFT,
+        };
+        let back_tab = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert_eq!(convert_key(key), back_tab);
+    }
+
+    #[test]
+    fn button_changes_become_presses_and_releases() {
+        let mut input = TermwizInput {
+            terminal: Weak::new(),
+            buttons: MouseButtons::NONE,
+        };
+        let left = MouseButton::Left;
+        assert_eq!(
+            mouse(&mut input, MouseButtons::LEFT),
+            MouseEventKind::Down(left)
+        );
+        assert_eq!(
+            mouse(&mut input, MouseButtons::LEFT),
+            MouseEventKind::Drag(left)
+        );
+        assert_eq!(
+            mouse(&mut input, MouseButtons::NONE),
+            MouseEventKind::Up(left)
+        );
+        assert_eq!(mouse(&mut input, MouseButtons::NONE), MouseEventKind::Moved);
+    }
+}
@@ -1,8 +1,14 @@
 use ratatui::{
     style::{Color, Modifier, Style},
+    text::Span,
     widgets::{Block, Borders},
 };
 
+use crate::{
+    caps,
+    theme::{self, Role},
+};
+
 /// Dim a blocks borders and contents
 pub trait Dim {
     fn dim(self, dim: bool) -> Block<'static>;
@@ -14,30 +20,49 @@ impl Dim for Block<'static> {
         if !dim {
             return self;
         }
-        let style = Style::default().fg(Color::Indexed(8));
+        let style = theme::style(Role::Dimmed);
         self.border_style(style).style(style)
     }
 }
 
 // FIXME: refactor all the below into cleaner, faster code
 /// Construct a block with a bold border and title
-pub fn bold_block(title: &'static str, border_fg: Color) -> Block {
-    let border_style = Style::default().fg(border_fg).add_modifier(Modifier::BOLD);
+pub fn bold_block(title: &'static str, border_fg: Color) -> Block<'static> {
+    let border_style = caps::current()
+        .adapt_style(Style::default().fg(border_fg))
+        .add_modifier(Modifier::BOLD);
     block_constructor(title, Some(border_style), Some(Borders::ALL))
 }
 
 /// Construct a block with a border and title
-pub fn default_block(title: &'static str, border_fg: Color) -> Block {
-    let border_style = Style::default().fg(border_fg);
+pub fn default_block(title: &'static str, border_fg: Color) -> Block<'static> {
+    let border_style = caps::current().adapt_style(Style::default().fg(border_fg));
     block_constructor(title, Some(border_style), Some(Borders::ALL))
 }
 
+/// Construct a block with a title and the focused border of the active theme
+pub fn themed_bold_block(title: &'static str) -> Block<'static> {
+    themed_block(title, Role::BorderFocused)
+}
+
+/// Construct a block with a title and the border of the active theme
+pub fn themed_default_block(title: &'static str) -> Block<'static> {
+    themed_block(title, Role::Border)
+}
+
+fn themed_block(title: &'static str, border: Role) -> Block<'static> {
+    Block::default()
+        .title(Span::styled(title, theme::style(Role::Title)))
+        .border_style(theme::style(border))
+        .borders(Borders::ALL)
+}
+
 /// Basically same thing as just constructing the block
 pub fn block_constructor(
     title: &'static str,
     border_style: Option<Style>,
     borders: Option<Borders>,
-) -> Block {
+) -> Block<'static> {
     let mut block = Block::default().title(title);
 
     if let Some(s) = border_style {
@@ -0,0 +1,383 @@
+use std::{
+    env,
+    sync::{PoisonError, RwLock},
+    time::Duration,
+};
+
+use ratatui::style::{Color, Style};
+
+use crate::backend::TermBackend;
+
+/// How many colors the terminal can show, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColorSupport {
+    /// Colors are unsupported or turned off with `NO_COLOR`
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+/// What the terminal supports. `detect` guesses from the environment,
+/// `query` asks the terminal itself for what it can answer. The colors of
+/// `current` are consulted by `theme::style`, its unicode support by `keys`
+/// and the divider of `split`.
+/// ```no_run
+/// use std::time::Duration;
+/// use tui_utils::{caps, term};
+///
+/// let mut terminal = term::init().unwrap();
+/// let caps = caps::detect()
+///     .query(terminal.backend_mut(), Duration::from_millis(100))
+///     .unwrap();
+/// caps::set(caps);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+    pub color: ColorSupport,
+    /// Symbols like the key labels of `keys` and box drawing characters can
+    /// be shown. The borders of `blocks` are drawn either way.
+    pub unicode: bool,
+    /// The kitty keyboard protocol is understood, see
+    /// `InitOptions::keyboard_enhancement`
+    pub kitty_keyboard: bool,
+    /// Synchronized output (mode 2026) is understood
+    pub synchronized_output: bool,
+}
+
+/// Terminals known to support the kitty keyboard protocol, by `TERM` prefix
+const KITTY_KEYBOARD_TERMS: &[&str] = &["xterm-kitty", "xterm-ghostty", "foot", "wezterm"];
+/// Terminals known to support synchronized output, by `TERM` prefix
+const SYNC_TERMS: &[&str] = &[
+    "xterm-kitty",
+    "xterm-ghostty",
+    "foot",
+    "wezterm",
+    "alacritty",
+    "contour",
+];
+
+/// The kitty keyboard flags, synchronized output mode and primary device
+/// attributes queries. Every terminal answers the last one, so once its
+/// answer arrives the others are known to be unsupported if they were not
+/// answered before it.
+#[cfg(unix)]
+const QUERIES: &str = "\x1b[?u\x1b[?2026$p\x1b[c";
+
+impl Capabilities {
+    /// Guess the capabilities from `COLORTERM`, `TERM`, `NO_COLOR` and the
+    /// locale
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let term = var("TERM").unwrap_or_default();
+        let known = |terms: &[&str]| terms.iter().any(|t| term.starts_with(t));
+
+        let colorterm = var("COLORTERM").unwrap_or_default();
+        let color = if var("NO_COLOR").is_some_and(|v| !v.is_empty()) || term == "dumb" {
+            ColorSupport::None
+        } else if colorterm == "truecolor"
+            || colorterm == "24bit"
+            || term.ends_with("-direct")
+            || known(&["xterm-kitty", "xterm-ghostty", "wezterm"])
+        {
+            ColorSupport::TrueColor
+        } else if term.contains("256color") {
+            ColorSupport::Ansi256
+        } else {
+            ColorSupport::Ansi16
+        };
+
+        // the first one set decides, like for the locale itself
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .into_iter()
+            .find_map(|name| var(name).filter(|v| !v.is_empty()))
+            .unwrap_or_default()
+            .to_lowercase();
+        // the Windows console has no locale variables but shows unicode
+        let unicode = term != "linux"
+            && term != "dumb"
+            && (locale.contains("utf-8")
+                || locale.contains("utf8")
+                || (cfg!(windows) && locale.is_empty()));
+
+        Self {
+            color,
+            unicode,
+            kitty_keyboard: known(KITTY_KEYBOARD_TERMS),
+            synchronized_output: known(SYNC_TERMS),
+        }
+    }
+
+    /// Ask the terminal whether it supports the kitty keyboard protocol
+    /// and synchronized output, replacing the guesses from the environment.
+    /// The terminal has to be in raw mode, like after `term::init`, and
+    /// nothing else may be reading events yet. If the terminal does not
+    /// answer within `timeout` the guesses are kept. Queries are only sent
+    /// on unix, elsewhere this returns the capabilities unchanged.
+    pub fn query<B: TermBackend>(
+        self,
+        backend: &mut B,
+        timeout: Duration,
+    ) -> std::io::Result<Self> {
+        #[cfg(unix)]
+        {
+            backend.write_sequence(QUERIES)?;
+            match crate::backend::read_tty_reply(timeout, |reply| parse_replies(reply).is_some()) {
+                Ok(reply) => Ok(parse_replies(&reply).map_or(self, |answers| answers.apply(self))),
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => Ok(self),
+                Err(e) => Err(e),
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = (backend, timeout);
+            Ok(self)
+        }
+    }
+
+    /// Downgrade `color` to one the terminal can show
+    pub fn adapt_color(&self, color: Color) -> Color {
+        match (self.color, color) {
+            (ColorSupport::None, _) => Color::Reset,
+            (ColorSupport::TrueColor, _) => color,
+            (ColorSupport::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(rgb_to_256(r, g, b)),
+            (ColorSupport::Ansi16, Color::Rgb(r, g, b)) => nearest_ansi16(r, g, b),
+            (ColorSupport::Ansi16, Color::Indexed(i)) if i < 16 => ANSI16[i as usize].0,
+            (ColorSupport::Ansi16, Color::Indexed(i)) => {
+                let (r, g, b) = indexed_to_rgb(i);
+                nearest_ansi16(r, g, b)
+            }
+            _ => color,
+        }
+    }
+
+    /// Downgrade the colors of `style` to ones the terminal can show
+    pub fn adapt_style(&self, mut style: Style) -> Style {
+        style.fg = style.fg.map(|c| self.adapt_color(c));
+        style.bg = style.bg.map(|c| self.adapt_color(c));
+        style
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+/// What the terminal answered to `QUERIES`
+#[cfg(unix)]
+#[derive(Debug, PartialEq)]
+struct Answers {
+    kitty_keyboard: bool,
+    synchronized_output: bool,
+}
+
+#[cfg(unix)]
+impl Answers {
+    fn apply(self, caps: Capabilities) -> Capabilities {
+        Capabilities {
+            kitty_keyboard: self.kitty_keyboard,
+            synchronized_output: self.synchronized_output,
+            ..caps
+        }
+    }
+}
+
+/// Parse the answers to `QUERIES`, `None` until the device attributes
+/// have arrived
+#[cfg(unix)]
+fn parse_replies(reply: &[u8]) -> Option<Answers> {
+    let reply = String::from_utf8_lossy(reply);
+    let mut answers = Answers {
+        kitty_keyboard: false,
+        synchronized_output: false,
+    };
+    for sequence in reply.split('\x1b') {
+        let Some(sequence) = sequence.strip_prefix("[?") else {
+            continue;
+        };
+        if let Some(mode) = sequence.strip_prefix("2026;") {
+            // 1 and 2 are set and reset, 3 is permanently set
+            answers.synchronized_output = matches!(mode, "1$y" | "2$y" | "3$y");
+        } else if sequence.ends_with('u') {
+            answers.kitty_keyboard = true;
+        } else if sequence.ends_with('c') {
+            return Some(answers);
+        }
+    }
+    None
+}
+
+/// Colors 0 to 15 with the xterm default palette
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Channel values of the 6x6x6 color cube starting at index 16
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+fn nearest_ansi16(r: u8, g: u8, b: u8) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb, (r, g, b)))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |c: u8| {
+        (0..CUBE.len())
+            .min_by_key(|&i| (CUBE[i] as i32 - c as i32).abs())
+            .unwrap_or_default() as u8
+    };
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = 16 + 36 * ri + 6 * gi + bi;
+
+    // the grayscale ramp runs from 8 to 238 in steps of 10
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let step = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray = 232 + step;
+
+    if distance(indexed_to_rgb(gray), (r, g, b)) < distance(indexed_to_rgb(cube), (r, g, b)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16[index as usize].1,
+        16..=231 => {
+            let i = index - 16;
+            (
+                CUBE[(i / 36) as usize],
+                CUBE[(i / 6 % 6) as usize],
+                CUBE[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+static CURRENT: RwLock<Option<Capabilities>> = RwLock::new(None);
+
+/// Capabilities of the terminal detected from the environment
+pub fn detect() -> Capabilities {
+    Capabilities::from_env()
+}
+
+/// The capabilities consulted when choosing styles and key symbols. These
+/// are detected from the environment on first use unless `set` was called.
+pub fn current() -> Capabilities {
+    if let Some(caps) = *CURRENT.read().unwrap_or_else(PoisonError::into_inner) {
+        return caps;
+    }
+    *CURRENT
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get_or_insert_with(detect)
+}
+
+/// Replace the capabilities returned by `current`, e.g. with queried ones
+pub fn set(caps: Capabilities) {
+    *CURRENT.write().unwrap_or_else(PoisonError::into_inner) = Some(caps);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ratatui::style::Color;
+
+    use super::{Capabilities, ColorSupport};
+
+    fn caps(vars: &[(&str, &str)]) -> Capabilities {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        Capabilities::from_vars(|name| vars.get(name).map(|v| v.to_string()))
+    }
+
+    #[test]
+    fn colors_from_env() {
+        let truecolor = caps(&[("TERM", "xterm-256color"), ("COLORTERM", "truecolor")]);
+        assert_eq!(truecolor.color, ColorSupport::TrueColor);
+        assert_eq!(
+            caps(&[("TERM", "screen-256color")]).color,
+            ColorSupport::Ansi256
+        );
+        assert_eq!(caps(&[("TERM", "xterm")]).color, ColorSupport::Ansi16);
+        let no_color = caps(&[("TERM", "xterm-256color"), ("NO_COLOR", "1")]);
+        assert_eq!(no_color.color, ColorSupport::None);
+        let empty_no_color = caps(&[("TERM", "xterm-256color"), ("NO_COLOR", "")]);
+        assert_eq!(empty_no_color.color, ColorSupport::Ansi256);
+    }
+
+    #[test]
+    fn unicode_from_locale() {
+        let utf8 = [("TERM", "xterm"), ("LC_ALL", ""), ("LANG", "en_US.UTF-8")];
+        assert!(caps(&utf8).unicode);
+        assert!(!caps(&[("TERM", "linux"), ("LANG", "en_US.UTF-8")]).unicode);
+        assert!(!caps(&[("TERM", "xterm"), ("LANG", "C")]).unicode);
+    }
+
+    #[test]
+    fn colors_are_downgraded() {
+        let mut caps = caps(&[("TERM", "xterm-256color")]);
+        assert_eq!(caps.adapt_color(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(
+            caps.adapt_color(Color::Rgb(128, 128, 128)),
+            Color::Indexed(244)
+        );
+
+        caps.color = ColorSupport::Ansi16;
+        assert_eq!(caps.adapt_color(Color::Rgb(250, 10, 10)), Color::LightRed);
+        assert_eq!(caps.adapt_color(Color::Indexed(8)), Color::DarkGray);
+        assert_eq!(caps.adapt_color(Color::Blue), Color::Blue);
+
+        caps.color = ColorSupport::None;
+        assert_eq!(caps.adapt_color(Color::Blue), Color::Reset);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn query_replies_parse() {
+        use super::{parse_replies, Answers};
+
+        let all = b"\x1b[?1u\x1b[?2026;2$y\x1b[?62;22c";
+        let expected = Answers {
+            kitty_keyboard: true,
+            synchronized_output: true,
+        };
+        assert_eq!(parse_replies(all), Some(expected));
+
+        let none = parse_replies(b"\x1b[?2026;0$y\x1b[?1;2c").unwrap();
+        assert!(!none.kitty_keyboard && !none.synchronized_output);
+        assert_eq!(parse_replies(b"\x1b[?1u"), None);
+    }
+}
@@ -1,7 +1,74 @@
-use std::error::Error;
+use std::{error::Error, io, time::Duration};
 
-use crossterm::event::KeyEvent;
 use ratatui::{backend::Backend, Frame};
+use thiserror::Error;
+
+use crate::{event::MouseEvent, keys::KeyEvent, term::TermError};
+
+/// Boxed error carried by `ComponentError`
+pub type BoxError = Box<dyn Error + Send + Sync>;
+
+/// Errors returned from component methods, split by whether the
+/// application can keep running after them.
+#[derive(Error, Debug)]
+pub enum ComponentError {
+    /// The application can carry on, for example after invalid input.
+    /// These are meant to be shown to the user in a status area.
+    #[error("{0}")]
+    Recoverable(BoxError),
+    /// The application cannot carry on. The terminal should be restored
+    /// and the error printed.
+    #[error("{0}")]
+    Fatal(BoxError),
+}
+
+impl ComponentError {
+    /// Wrap an error the application can recover from
+    /// ```
+    /// use tui_utils::component::ComponentError;
+    ///
+    /// let err = ComponentError::recoverable("no item selected");
+    /// assert!(err.is_recoverable());
+    /// ```
+    pub fn recoverable<E: Into<BoxError>>(error: E) -> Self {
+        Self::Recoverable(error.into())
+    }
+
+    /// Wrap an error the application cannot recover from
+    pub fn fatal<E: Into<BoxError>>(error: E) -> Self {
+        Self::Fatal(error.into())
+    }
+
+    pub fn is_recoverable(&self) -> bool {
+        matches!(self, Self::Recoverable(_))
+    }
+
+    /// Send the error where it belongs. Recoverable errors are handed to
+    /// `on_recoverable`, for example to be shown in a status area, and the
+    /// application should continue. Fatal errors are returned, so the
+    /// application can exit and drop its `TerminalGuard` before printing them.
+    pub fn route<F: FnOnce(BoxError)>(self, on_recoverable: F) -> Result<(), BoxError> {
+        match self {
+            Self::Recoverable(e) => {
+                on_recoverable(e);
+                Ok(())
+            }
+            Self::Fatal(e) => Err(e),
+        }
+    }
+}
+
+impl From<TermError> for ComponentError {
+    fn from(e: TermError) -> Self {
+        Self::Fatal(e.into())
+    }
+}
+
+impl From<io::Error> for ComponentError {
+    fn from(e: io::Error) -> Self {
+        Self::Fatal(e.into())
+    }
+}
 
 /// Trait for implementing components
 pub trait Component {
@@ -20,7 +87,67 @@ pub trait Component {
     /// Take care of any input handling here. This method is not
     /// required when implementing `Component` in case your component
     /// does not require input handling.
-    fn handle_input(&mut self, _key: KeyEvent) -> Result<Self::Message, Box<dyn Error>> {
+    fn handle_input(&mut self, _key: KeyEvent) -> Result<Self::Message, ComponentError> {
         Ok(Default::default())
     }
+    /// Handle mouse events. Like `handle_input` this is optional and
+    /// only receives events if mouse capture is enabled.
+    fn handle_mouse(&mut self, _mouse: MouseEvent) -> Result<Self::Message, ComponentError> {
+        Ok(Default::default())
+    }
+    /// Called by the event loop with recoverable errors returned from the
+    /// component, for example to show them in a status area. By default the
+    /// error is escalated to a fatal one which stops the event loop.
+    fn handle_error(&mut self, error: BoxError) -> Result<Self::Message, ComponentError> {
+        Err(ComponentError::Fatal(error))
+    }
+    /// Called once before the component is drawn for the first time.
+    /// Parent components should call this on their children when
+    /// they start showing them.
+    fn mount(&mut self) {}
+    /// Called when the component is no longer shown, for example
+    /// when the event loop exits or a parent hides it.
+    fn unmount(&mut self) {}
+    /// Called periodically by the event loop. `elapsed` is the time
+    /// since the previous tick, which is useful for animations and timers.
+    fn tick(&mut self, _elapsed: Duration) -> Self::Message {
+        Default::default()
+    }
+    /// Called by the event loop when the terminal is resized.
+    fn resize(&mut self, _width: u16, _height: u16) -> Self::Message {
+        Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::ComponentError;
+    use crate::term::TermError;
+
+    #[test]
+    fn recoverable_is_routed_to_handler() {
+        let mut status = None;
+        let routed =
+            ComponentError::recoverable("list is full").route(|e| status = Some(e.to_string()));
+        assert!(routed.is_ok());
+        assert_eq!(status.as_deref(), Some("list is full"));
+    }
+
+    #[test]
+    fn fatal_is_returned_from_route() {
+        let mut handled = false;
+        let routed = ComponentError::fatal("disk full").route(|_| handled = true);
+        assert_eq!(routed.unwrap_err().to_string(), "disk full");
+        assert!(!handled);
+    }
+
+    #[test]
+    fn terminal_errors_are_fatal() {
+        let err: ComponentError = TermError::Poll(String::from("oops")).into();
+        assert!(!err.is_recoverable());
+        let err: ComponentError = io::Error::other("oops").into();
+        assert!(!err.is_recoverable());
+    }
 }
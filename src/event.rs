@@ -0,0 +1,512 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, SendError, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    backend::{DefaultInput, EventInput},
+    keys::{KeyEvent, KeyModifiers},
+    term::TermError,
+    EVENT_TIMEOUT,
+};
+
+/// Input read from the terminal, independent of the backend
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Event {
+    Key(KeyEvent),
+    /// Only reported with mouse capture enabled
+    Mouse(MouseEvent),
+    /// The terminal was resized to the given width and height
+    Resize(u16, u16),
+    /// Pasted text, only reported with bracketed paste enabled
+    Paste(String),
+    /// Only reported with focus change enabled
+    FocusGained,
+    FocusLost,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MouseEvent {
+    pub kind: MouseEventKind,
+    /// Zero-based column of the pointer
+    pub column: u16,
+    /// Zero-based row of the pointer
+    pub row: u16,
+    pub modifiers: KeyModifiers,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseEventKind {
+    Down(MouseButton),
+    Up(MouseButton),
+    /// The pointer moved while the button was held down
+    Drag(MouseButton),
+    Moved,
+    ScrollDown,
+    ScrollUp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+/// Events produced by `EventSource` and `EventReader`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppEvent<M = ()> {
+    /// Input from the terminal such as key presses, mouse events and resizes
+    Input(Event),
+    /// The tick rate has passed. Carries the time since the previous tick.
+    Tick(Duration),
+    /// A message sent through a `MessageSender`, for example from a worker thread
+    Message(M),
+}
+
+/// Reads terminal events in batches and emits `AppEvent::Tick` at a
+/// configurable rate, which makes it suitable for animations and clocks.
+/// Input is read through the default backend unless another `EventInput`
+/// is passed to `with_input`.
+/// ```no_run
+/// use std::time::Duration;
+/// use tui_utils::event::{AppEvent, EventSource};
+///
+/// let mut events = EventSource::new(Duration::from_millis(50));
+/// for event in events.next_batch::<()>().unwrap() {
+///     match event {
+///         AppEvent::Input(ev) => println!("{ev:?}"),
+///         AppEvent::Tick(elapsed) => println!("tick after {elapsed:?}"),
+///         AppEvent::Message(()) => {}
+///     }
+/// }
+/// ```
+pub struct EventSource<I = DefaultInput> {
+    tick_rate: Duration,
+    last_tick: Instant,
+    input: I,
+    /// Read error held back so the input before it could be returned first
+    failed: Option<TermError>,
+}
+
+impl Default for EventSource {
+    fn default() -> Self {
+        Self::new(EVENT_TIMEOUT)
+    }
+}
+
+impl EventSource {
+    pub fn new(tick_rate: Duration) -> Self {
+        Self::with_input(tick_rate, DefaultInput::default())
+    }
+}
+
+impl<I: EventInput> EventSource<I> {
+    /// Read input from `input` instead of the default backend
+    pub fn with_input(tick_rate: Duration, input: I) -> Self {
+        Self {
+            tick_rate,
+            last_tick: Instant::now(),
+            input,
+            failed: None,
+        }
+    }
+
+    pub fn tick_rate(&self) -> Duration {
+        self.tick_rate
+    }
+
+    /// Block until input arrives or the next tick is due, then drain all
+    /// input that is already pending without blocking again. Consecutive
+    /// resize events are coalesced into the last one, and a `Tick` is
+    /// appended if the tick rate has passed. Handling a whole batch before
+    /// drawing keeps the application from lagging behind during key repeat.
+    /// If reading fails after some input was read, that input is returned
+    /// and the error is returned by the next call.
+    pub fn next_batch<M>(&mut self) -> Result<Vec<AppEvent<M>>, TermError> {
+        if let Some(e) = self.failed.take() {
+            return Err(e);
+        }
+        let mut input = vec![];
+        let timeout = self.tick_rate.saturating_sub(self.last_tick.elapsed());
+        if let Some(event) = self.input.poll_event(timeout)? {
+            input.push(event);
+            loop {
+                match self.input.poll_event(Duration::ZERO) {
+                    Ok(Some(event)) => input.push(event),
+                    Ok(None) => break,
+                    Err(e) => {
+                        self.failed = Some(e);
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut batch = coalesce(input.into_iter().map(AppEvent::Input).collect());
+        let elapsed = self.last_tick.elapsed();
+        if elapsed >= self.tick_rate {
+            self.last_tick = Instant::now();
+            batch.push(AppEvent::Tick(elapsed));
+        }
+        Ok(batch)
+    }
+}
+
+/// How often the reader thread checks whether it should stop
+const READER_POLL: Duration = Duration::from_millis(50);
+
+type Delivery<M> = Result<AppEvent<M>, TermError>;
+
+/// Sends user defined messages to an `EventReader` from any thread. Cloning
+/// it is cheap.
+pub struct MessageSender<M> {
+    sender: Sender<Delivery<M>>,
+}
+
+impl<M> Clone for MessageSender<M> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<M> MessageSender<M> {
+    /// Deliver `message` as `AppEvent::Message`. This fails and hands the
+    /// message back if the `EventReader` was dropped.
+    pub fn send(&self, message: M) -> Result<(), SendError<M>> {
+        self.sender
+            .send(Ok(AppEvent::Message(message)))
+            .map_err(|SendError(e)| match e {
+                Ok(AppEvent::Message(m)) => SendError(m),
+                _ => unreachable!("only messages are sent through a MessageSender"),
+            })
+    }
+}
+
+/// Reads terminal events on a dedicated thread and delivers them through a
+/// channel together with ticks and messages from `MessageSender`s. This
+/// lets an application wait on terminal input and worker threads at once
+/// without a busy loop. The thread is stopped when the reader is dropped.
+/// ```no_run
+/// use std::{thread, time::Duration};
+/// use tui_utils::event::{AppEvent, EventReader};
+///
+/// let mut events = EventReader::new(Duration::from_millis(250));
+/// let sender = events.sender();
+/// thread::spawn(move || sender.send(String::from("work done")));
+///
+/// for event in events.next_batch().unwrap() {
+///     if let AppEvent::Message(message) = event {
+///         println!("{message}");
+///     }
+/// }
+/// ```
+pub struct EventReader<M> {
+    tick_rate: Duration,
+    last_tick: Instant,
+    sender: Sender<Delivery<M>>,
+    receiver: Receiver<Delivery<M>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    /// Error held back so the events delivered before it could be returned first
+    failed: Option<TermError>,
+    /// Set once an error was returned, after which the thread no longer reads
+    stopped: bool,
+}
+
+impl<M: Send + 'static> EventReader<M> {
+    /// Start reading terminal events on a new thread, ticking every `tick_rate`
+    pub fn new(tick_rate: Duration) -> Self {
+        Self::with_input(tick_rate, DefaultInput::default())
+    }
+
+    /// Start a reader thread which takes its input from `input` instead of
+    /// the default backend
+    pub fn with_input<I>(tick_rate: Duration, input: I) -> Self
+    where
+        I: EventInput + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let (sender, stop) = (sender.clone(), stop.clone());
+            thread::spawn(move || read_events(input, sender, stop))
+        };
+        Self {
+            tick_rate,
+            last_tick: Instant::now(),
+            sender,
+            receiver,
+            stop,
+            thread: Some(thread),
+            failed: None,
+            stopped: false,
+        }
+    }
+}
+
+impl<M> EventReader<M> {
+    /// Create a sender for delivering messages from other threads
+    pub fn sender(&self) -> MessageSender<M> {
+        MessageSender {
+            sender: self.sender.clone(),
+        }
+    }
+
+    pub fn tick_rate(&self) -> Duration {
+        self.tick_rate
+    }
+
+    /// Block until an event arrives or the next tick is due. Reading the
+    /// terminal stops after an error, so every call after one fails too.
+    pub fn recv(&mut self) -> Result<AppEvent<M>, TermError> {
+        if self.stopped {
+            return Err(TermError::Poll(String::from(
+                "input is no longer read after an error",
+            )));
+        }
+        let delivery = match self.failed.take() {
+            Some(e) => Err(e),
+            None => {
+                let timeout = self.tick_rate.saturating_sub(self.last_tick.elapsed());
+                match self.receiver.recv_timeout(timeout) {
+                    Ok(delivery) => delivery,
+                    // the reader holds a sender itself, so this only times out
+                    Err(_) => Ok(self.tick()),
+                }
+            }
+        };
+        self.stopped = delivery.is_err();
+        delivery
+    }
+
+    /// Like `EventSource::next_batch`: block for the first event, then drain
+    /// everything already delivered, coalesce consecutive resizes and append
+    /// a `Tick` if the tick rate has passed. Like there, an error delivered
+    /// after other events is returned by the next call.
+    pub fn next_batch(&mut self) -> Result<Vec<AppEvent<M>>, TermError> {
+        let mut batch = vec![];
+        match self.recv()? {
+            AppEvent::Tick(elapsed) => return Ok(vec![AppEvent::Tick(elapsed)]),
+            event => batch.push(event),
+        }
+        while let Ok(delivery) = self.receiver.try_recv() {
+            match delivery {
+                Ok(event) => batch.push(event),
+                Err(e) => {
+                    self.failed = Some(e);
+                    break;
+                }
+            }
+        }
+
+        let mut batch = coalesce(batch);
+        if self.last_tick.elapsed() >= self.tick_rate {
+            batch.push(self.tick());
+        }
+        Ok(batch)
+    }
+
+    fn tick(&mut self) -> AppEvent<M> {
+        let elapsed = self.last_tick.elapsed();
+        self.last_tick = Instant::now();
+        AppEvent::Tick(elapsed)
+    }
+}
+
+impl<M> Drop for EventReader<M> {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Body of the reader thread. Polls with a short timeout so it notices
+/// when it should stop.
+fn read_events<M, I>(mut input: I, sender: Sender<Delivery<M>>, stop: Arc<AtomicBool>)
+where
+    I: EventInput,
+{
+    while !stop.load(Ordering::Relaxed) {
+        let delivery = match input.poll_event(READER_POLL) {
+            Ok(None) => continue,
+            Ok(Some(event)) => Ok(AppEvent::Input(event)),
+            Err(e) => Err(e),
+        };
+        let failed = delivery.is_err();
+        if sender.send(delivery).is_err() || failed {
+            return;
+        }
+    }
+}
+
+/// Collapse runs of resize events into the last one of each run, since
+/// only the final size matters for drawing.
+fn coalesce<M>(events: Vec<AppEvent<M>>) -> Vec<AppEvent<M>> {
+    let mut out: Vec<AppEvent<M>> = Vec::with_capacity(events.len());
+    for event in events {
+        match (out.last_mut(), &event) {
+            (
+                Some(last @ AppEvent::Input(Event::Resize(..))),
+                AppEvent::Input(Event::Resize(..)),
+            ) => *last = event,
+            _ => out.push(event),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::{coalesce, AppEvent, Event, EventReader, EventSource};
+    use crate::{
+        keys::{KeyCode, KeyEvent},
+        term::TermError,
+    };
+
+    /// Reader fed by `events` instead of the terminal
+    fn reader<M: Send + 'static>(tick_rate: Duration, events: Vec<Event>) -> EventReader<M> {
+        let mut events = events.into_iter();
+        EventReader::with_input(tick_rate, move |timeout: Duration| match events.next() {
+            Some(event) => Ok(Some(event)),
+            None => {
+                thread::sleep(timeout);
+                Ok(None)
+            }
+        })
+    }
+
+    fn key(c: char) -> AppEvent {
+        AppEvent::Input(Event::Key(KeyEvent::from(KeyCode::Char(c))))
+    }
+
+    fn resize(width: u16, height: u16) -> AppEvent {
+        AppEvent::Input(Event::Resize(width, height))
+    }
+
+    #[test]
+    fn consecutive_resizes_are_coalesced() {
+        let events = vec![resize(10, 10), resize(20, 10), resize(30, 15)];
+        assert_eq!(coalesce(events), vec![resize(30, 15)]);
+    }
+
+    #[test]
+    fn resizes_separated_by_input_are_kept() {
+        let events = vec![resize(10, 10), key('a'), resize(20, 10), resize(30, 10)];
+        assert_eq!(
+            coalesce(events),
+            vec![resize(10, 10), key('a'), resize(30, 10)]
+        );
+    }
+
+    #[test]
+    fn input_order_is_kept() {
+        let events = vec![key('a'), key('b'), key('c')];
+        assert_eq!(coalesce(events.clone()), events);
+    }
+
+    #[test]
+    fn source_drains_pending_input() {
+        let mut events =
+            vec![Event::Resize(1, 1), Event::Resize(2, 2), Event::FocusLost].into_iter();
+        let mut source =
+            EventSource::with_input(
+                Duration::from_secs(60),
+                move |_: Duration| Ok(events.next()),
+            );
+        assert_eq!(
+            source.next_batch::<()>().unwrap(),
+            vec![resize(2, 2), AppEvent::Input(Event::FocusLost)]
+        );
+    }
+
+    #[test]
+    fn messages_from_other_threads_are_delivered() {
+        let mut reader = reader(Duration::from_secs(60), vec![]);
+        let sender = reader.sender();
+        let worker = sender.clone();
+        thread::spawn(move || worker.send(1).unwrap())
+            .join()
+            .unwrap();
+        sender.send(2).unwrap();
+
+        assert_eq!(
+            reader.next_batch().unwrap(),
+            vec![AppEvent::Message(1), AppEvent::Message(2)]
+        );
+    }
+
+    #[test]
+    fn ticks_when_nothing_arrives() {
+        let mut reader = reader::<()>(Duration::from_millis(10), vec![]);
+        assert!(matches!(reader.recv().unwrap(), AppEvent::Tick(_)));
+    }
+
+    #[test]
+    fn terminal_events_are_delivered() {
+        let events = vec![Event::FocusGained, Event::Resize(1, 1), Event::Resize(2, 2)];
+        let mut reader = reader::<()>(Duration::from_secs(60), events);
+        let mut received = vec![];
+        while received.last() != Some(&resize(2, 2)) {
+            received.extend(reader.next_batch().unwrap());
+        }
+        assert_eq!(received[0], AppEvent::Input(Event::FocusGained));
+    }
+
+    #[test]
+    fn read_errors_are_delivered() {
+        let mut reader = EventReader::<()>::with_input(Duration::from_secs(60), |_: Duration| {
+            Err(TermError::Poll(String::from("gone")))
+        });
+        assert!(reader.recv().is_err());
+        // the reader thread is gone, polling again must not just tick
+        assert!(reader.recv().is_err());
+        assert!(reader.next_batch().is_err());
+    }
+
+    #[test]
+    fn events_before_an_error_are_not_lost() {
+        let mut events = vec![
+            Ok(Some(Event::FocusGained)),
+            Err(TermError::Poll(String::from("gone"))),
+            Ok(Some(Event::FocusLost)),
+        ]
+        .into_iter();
+        let mut source = EventSource::with_input(Duration::from_secs(60), move |_: Duration| {
+            events.next().unwrap_or(Ok(None))
+        });
+        let batch = source.next_batch::<()>().unwrap();
+        assert_eq!(batch, vec![AppEvent::Input(Event::FocusGained)]);
+        assert!(source.next_batch::<()>().is_err());
+        let batch = source.next_batch::<()>().unwrap();
+        assert_eq!(batch, vec![AppEvent::Input(Event::FocusLost)]);
+
+        let mut reader = reader(Duration::from_secs(60), vec![]);
+        reader.sender.send(Ok(AppEvent::Message(1))).unwrap();
+        reader
+            .sender
+            .send(Err(TermError::Poll(String::from("gone"))))
+            .unwrap();
+        assert_eq!(reader.next_batch().unwrap(), vec![AppEvent::Message(1)]);
+        assert!(reader.next_batch().is_err());
+    }
+
+    #[test]
+    fn sending_after_drop_returns_message() {
+        let reader = reader(Duration::from_secs(60), vec![]);
+        let sender = reader.sender();
+        drop(reader);
+        assert_eq!(sender.send(3).unwrap_err().0, 3);
+    }
+}
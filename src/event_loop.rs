@@ -0,0 +1,309 @@
+use std::time::Duration;
+
+use crate::{
+    backend::TermBackend,
+    component::{Component, ComponentError},
+    event::{AppEvent, Event, EventReader, EventSource},
+    keys::{key_match, KeyCode, KeyEvent, KeyModifiers, Keybind},
+    term::{TermError, TerminalGuard},
+    EVENT_TIMEOUT,
+};
+
+/// Returned from the message handler passed to `EventLoop::run` to
+/// tell the loop whether it should keep going.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    Exit,
+}
+
+/// Binding used by `EventLoop::suspend`
+pub const DEFAULT_SUSPEND_KEY: Keybind = Keybind::new(KeyCode::Char('z'), KeyModifiers::CONTROL);
+
+/// Drives a root `Component`: draws it, forwards key, mouse and resize events
+/// and calls its `tick` hook at a fixed rate. All pending events are handled
+/// before the next draw, see `EventSource`. The component is mounted
+/// before the first draw and unmounted when the loop exits.
+pub struct EventLoop {
+    tick_rate: Duration,
+    suspend_key: Option<Keybind>,
+}
+
+impl Default for EventLoop {
+    fn default() -> Self {
+        Self::new(EVENT_TIMEOUT)
+    }
+}
+
+impl EventLoop {
+    /// Creates an event loop which ticks the component every `tick_rate`
+    /// ```
+    /// use std::time::Duration;
+    /// use tui_utils::event_loop::EventLoop;
+    ///
+    /// // tick four times a second
+    /// let event_loop = EventLoop::new(Duration::from_millis(250));
+    /// ```
+    pub fn new(tick_rate: Duration) -> Self {
+        Self {
+            tick_rate,
+            suspend_key: None,
+        }
+    }
+
+    /// Suspend the application on Ctrl+Z like shells do, see `TerminalGuard::suspend`.
+    /// The key press is not forwarded to the component.
+    pub fn suspend(self, enable: bool) -> Self {
+        self.suspend_key(enable.then_some(DEFAULT_SUSPEND_KEY))
+    }
+
+    /// Like `suspend`, but with a custom binding
+    pub fn suspend_key(mut self, key: Option<Keybind>) -> Self {
+        self.suspend_key = key;
+        self
+    }
+
+    /// Run the loop until `update` returns `Flow::Exit` or a fatal error occurs.
+    /// Events are read through the backend of `terminal`. Every message produced by the component is passed to `update` together
+    /// with the component itself. Recoverable errors are passed to the
+    /// component's `handle_error` method.
+    pub fn run<B, C, F>(
+        &self,
+        terminal: &mut TerminalGuard<B>,
+        component: &mut C,
+        update: F,
+    ) -> Result<(), ComponentError>
+    where
+        B: TermBackend,
+        C: Component,
+        F: FnMut(&mut C, C::Message) -> Flow,
+    {
+        let mut events = EventSource::with_input(self.tick_rate, B::Input::default());
+        self.run_on(terminal, component, update, || events.next_batch())
+    }
+
+    /// Like `run`, but reads events from `reader` which also delivers
+    /// messages sent from other threads through its `MessageSender`s. These
+    /// are passed to `update` like messages from the component. The tick rate
+    /// of `reader` is used instead of the loop's.
+    pub fn run_with_reader<B, C, F>(
+        &self,
+        terminal: &mut TerminalGuard<B>,
+        reader: &mut EventReader<C::Message>,
+        component: &mut C,
+        update: F,
+    ) -> Result<(), ComponentError>
+    where
+        B: TermBackend,
+        C: Component,
+        F: FnMut(&mut C, C::Message) -> Flow,
+    {
+        self.run_on(terminal, component, update, || reader.next_batch())
+    }
+
+    fn run_on<B, C, F, E>(
+        &self,
+        terminal: &mut TerminalGuard<B>,
+        component: &mut C,
+        mut update: F,
+        next_batch: E,
+    ) -> Result<(), ComponentError>
+    where
+        B: TermBackend,
+        C: Component,
+        F: FnMut(&mut C, C::Message) -> Flow,
+        E: FnMut() -> Result<Vec<AppEvent<C::Message>>, TermError>,
+    {
+        component.mount();
+        let outcome = self.drive(terminal, component, &mut update, next_batch);
+        component.unmount();
+        outcome
+    }
+
+    fn drive<B, C, F, E>(
+        &self,
+        terminal: &mut TerminalGuard<B>,
+        component: &mut C,
+        update: &mut F,
+        mut next_batch: E,
+    ) -> Result<(), ComponentError>
+    where
+        B: TermBackend,
+        C: Component,
+        F: FnMut(&mut C, C::Message) -> Flow,
+        E: FnMut() -> Result<Vec<AppEvent<C::Message>>, TermError>,
+    {
+        loop {
+            terminal.draw(|f| component.draw(f, false))?;
+
+            // handle everything that is pending before drawing again
+            for event in next_batch()? {
+                let message = match event {
+                    AppEvent::Input(Event::Key(key)) if is_suspend(self.suspend_key, &key) => {
+                        terminal.suspend()?;
+                        None
+                    }
+                    AppEvent::Input(event) => dispatch(component, event)?,
+                    AppEvent::Tick(elapsed) => Some(component.tick(elapsed)),
+                    AppEvent::Message(message) => Some(message),
+                };
+                if let Some(message) = message {
+                    if update(component, message) == Flow::Exit {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn is_suspend(binding: Option<Keybind>, key: &KeyEvent) -> bool {
+    binding.is_some_and(|b| key_match(key, &b))
+}
+
+/// Forward a terminal event to the matching component method. Recoverable
+/// errors are handed to the component's `handle_error` method.
+pub(crate) fn dispatch<C: Component>(
+    component: &mut C,
+    event: Event,
+) -> Result<Option<C::Message>, ComponentError> {
+    let outcome = match event {
+        Event::Key(ev) => component.handle_input(ev),
+        Event::Mouse(ev) => component.handle_mouse(ev),
+        Event::Resize(width, height) => Ok(component.resize(width, height)),
+        // other events are not forwarded to components
+        _ => return Ok(None),
+    };
+    match outcome {
+        Err(ComponentError::Recoverable(e)) => component.handle_error(e).map(Some),
+        outcome => outcome.map(Some),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use ratatui::{
+        backend::{Backend, TestBackend},
+        Frame,
+    };
+
+    use super::{EventLoop, Flow};
+    use crate::{
+        component::{Component, ComponentError},
+        event::{Event, EventSource},
+        keys::{KeyCode, KeyEvent},
+        term::{InitOptions, TerminalGuard},
+    };
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    enum Message {
+        #[default]
+        Idle,
+        Exit,
+    }
+
+    /// Records the calls made by the loop. Exits on `q` or the third
+    /// tick, any other key is a fatal error.
+    #[derive(Default)]
+    struct Recorder {
+        calls: Vec<String>,
+        ticks: Vec<Duration>,
+    }
+
+    impl Component for Recorder {
+        type Message = Message;
+
+        fn draw<B: Backend>(&mut self, _f: &mut Frame<B>, _dim: bool) {
+            if self.calls.last().map(String::as_str) != Some("draw") {
+                self.calls.push(String::from("draw"));
+            }
+        }
+
+        fn handle_input(&mut self, key: KeyEvent) -> Result<Message, ComponentError> {
+            match key.code {
+                KeyCode::Char('q') => Ok(Message::Exit),
+                _ => Err(ComponentError::fatal("boom")),
+            }
+        }
+
+        fn mount(&mut self) {
+            self.calls.push(String::from("mount"));
+        }
+
+        fn unmount(&mut self) {
+            self.calls.push(String::from("unmount"));
+        }
+
+        fn tick(&mut self, elapsed: Duration) -> Message {
+            self.ticks.push(elapsed);
+            match self.ticks.len() {
+                3 => Message::Exit,
+                _ => Message::Idle,
+            }
+        }
+
+        fn resize(&mut self, width: u16, height: u16) -> Message {
+            self.calls.push(format!("resize {width}x{height}"));
+            Message::Idle
+        }
+    }
+
+    /// Run `component` on the events of `script`, followed by nothing but ticks
+    fn run(
+        tick_rate: Duration,
+        script: Vec<Event>,
+        component: &mut Recorder,
+    ) -> Result<(), ComponentError> {
+        let backend = TestBackend::new(20, 5);
+        let mut terminal = TerminalGuard::new(backend, InitOptions::default()).unwrap();
+        let mut script = script.into_iter();
+        let mut events = EventSource::with_input(tick_rate, move |timeout| match script.next() {
+            Some(event) => Ok(Some(event)),
+            None => {
+                thread::sleep(timeout);
+                Ok(None)
+            }
+        });
+        let update = |_: &mut Recorder, message| match message {
+            Message::Idle => Flow::Continue,
+            Message::Exit => Flow::Exit,
+        };
+        EventLoop::new(tick_rate).run_on(&mut terminal, component, update, || events.next_batch())
+    }
+
+    fn key(c: char) -> Event {
+        Event::Key(KeyEvent::from(KeyCode::Char(c)))
+    }
+
+    #[test]
+    fn mounts_before_drawing_and_forwards_resizes() {
+        let mut component = Recorder::default();
+        let script = vec![Event::Resize(30, 10), key('q')];
+        run(Duration::from_secs(60), script, &mut component).unwrap();
+        assert_eq!(
+            component.calls,
+            ["mount", "draw", "resize 30x10", "unmount"]
+        );
+    }
+
+    #[test]
+    fn unmounts_after_a_fatal_error() {
+        let mut component = Recorder::default();
+        let error = run(Duration::from_secs(60), vec![key('x')], &mut component).unwrap_err();
+        assert!(!error.is_recoverable());
+        assert_eq!(error.to_string(), "boom");
+        assert_eq!(component.calls.last().map(String::as_str), Some("unmount"));
+    }
+
+    #[test]
+    fn ticks_at_the_tick_rate() {
+        let tick_rate = Duration::from_millis(20);
+        let mut component = Recorder::default();
+        run(tick_rate, vec![], &mut component).unwrap();
+        assert_eq!(component.ticks.len(), 3);
+        assert!(component.ticks.iter().all(|&elapsed| elapsed >= tick_rate));
+        assert_eq!(component.calls, ["mount", "draw", "unmount"]);
+    }
+}
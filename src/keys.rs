@@ -1,24 +1,102 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
-// Carbon copy of crossterm's `KeyEvent` which allows for easier construction and also easy comparison when input handling.
-#[derive(Deserialize, Serialize, Debug)]
-pub struct Keybind {
+use crate::caps;
+
+/// A key on the keyboard, independent of the terminal backend. The variants
+/// and their serialized form mirror crossterm's `KeyCode`, so existing
+/// keymaps keep working.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyCode {
+    Backspace,
+    Enter,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Tab,
+    BackTab,
+    Delete,
+    Insert,
+    /// Function key, `F(1)` is F1
+    F(u8),
+    Char(char),
+    /// A key the backend could not map to any of the other variants
+    Null,
+    Esc,
+    CapsLock,
+    ScrollLock,
+    NumLock,
+    PrintScreen,
+    Pause,
+    Menu,
+    KeypadBegin,
+}
+
+bitflags! {
+    /// Modifier keys held down during a key or mouse event
+    #[derive(Deserialize, Serialize)]
+    pub struct KeyModifiers: u8 {
+        const SHIFT = 0b0000_0001;
+        const CONTROL = 0b0000_0010;
+        const ALT = 0b0000_0100;
+        const SUPER = 0b0000_1000;
+        const HYPER = 0b0001_0000;
+        const META = 0b0010_0000;
+        const NONE = 0b0000_0000;
+    }
+}
+
+/// Whether a key was pressed, repeated or released. Only terminals with
+/// keyboard enhancement enabled report anything but `Press`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum KeyEventKind {
+    #[default]
+    Press,
+    Repeat,
+    Release,
+}
+
+/// A key event as delivered by any of the terminal backends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
     pub code: KeyCode,
     pub modifiers: KeyModifiers,
+    pub kind: KeyEventKind,
 }
 
-impl Keybind {
+impl KeyEvent {
     pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
-        Self { code, modifiers }
+        Self {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+        }
     }
 }
 
-impl PartialEq for Keybind {
-    fn eq(&self, other: &Self) -> bool {
-        let ev: KeyEvent = self.into();
-        let other: KeyEvent = other.into();
-        ev == other
+impl From<KeyCode> for KeyEvent {
+    fn from(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+}
+
+/// A key combination which allows for easy construction and comparison when
+/// handling input. Key events of every backend are matched against it.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Keybind {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Keybind {
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
     }
 }
 
@@ -28,31 +106,83 @@ impl From<&Keybind> for KeyEvent {
     }
 }
 
-impl ToString for Keybind {
-    fn to_string(&self) -> String {
-        let key = match self.code {
-            KeyCode::Char(c) if c == ' ' => '\u{23b5}',
-            KeyCode::Char(c) => c,
-            KeyCode::Tab => '\u{21e5}',
-            KeyCode::BackTab => '\u{21e4}',
-            KeyCode::Esc => '\u{238b}',
-            KeyCode::Enter => '\u{23ce}',
-            KeyCode::Up => '\u{2191}',
-            KeyCode::Down => '\u{2193}',
-            KeyCode::Left => '\u{2190}',
-            KeyCode::Right => '\u{2192}',
-            _ => '\u{2327}',
+impl Keybind {
+    /// The key as symbols, or as ASCII names for terminals without unicode
+    fn label(&self, unicode: bool) -> String {
+        let key = match (self.code, unicode) {
+            (KeyCode::Char(' '), true) => "\u{23b5}".into(),
+            (KeyCode::Char(' '), false) => "Space".into(),
+            (KeyCode::Char(c), _) => c.to_string(),
+            (KeyCode::Tab, true) => "\u{21e5}".into(),
+            (KeyCode::Tab, false) => "Tab".into(),
+            (KeyCode::BackTab, true) => "\u{21e4}".into(),
+            (KeyCode::BackTab, false) => "S-Tab".into(),
+            (KeyCode::Esc, true) => "\u{238b}".into(),
+            (KeyCode::Esc, false) => "Esc".into(),
+            (KeyCode::Enter, true) => "\u{23ce}".into(),
+            (KeyCode::Enter, false) => "Enter".into(),
+            (KeyCode::Up, true) => "\u{2191}".into(),
+            (KeyCode::Up, false) => "Up".into(),
+            (KeyCode::Down, true) => "\u{2193}".into(),
+            (KeyCode::Down, false) => "Down".into(),
+            (KeyCode::Left, true) => "\u{2190}".into(),
+            (KeyCode::Left, false) => "Left".into(),
+            (KeyCode::Right, true) => "\u{2192}".into(),
+            (KeyCode::Right, false) => "Right".into(),
+            (_, true) => "\u{2327}".into(),
+            (_, false) => "?".into(),
         };
         match self.modifiers {
-            KeyModifiers::SHIFT if self.code == KeyCode::BackTab => key.to_string(),
-            KeyModifiers::SHIFT => format!("\u{21e7}{key}"),
+            KeyModifiers::SHIFT if self.code == KeyCode::BackTab => key,
+            KeyModifiers::SHIFT if unicode => format!("\u{21e7}{key}"),
+            KeyModifiers::SHIFT => format!("S-{key}"),
             KeyModifiers::CONTROL => format!("^{key}"),
-            _ => key.to_string(),
+            _ => key,
         }
     }
 }
 
-/// Helper function to figure out if a specific key was pressed.
+/// Keys are shown as symbols, or as ASCII names if `caps::current` says
+/// the terminal cannot show unicode.
+impl fmt::Display for Keybind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.label(caps::current().unicode))
+    }
+}
+
+/// Helper function to figure out if a specific key was pressed. Key releases
+/// never match.
 pub fn key_match(ev: &KeyEvent, binding: &Keybind) -> bool {
-    ev.code == binding.code && ev.modifiers == binding.modifiers
+    ev.kind != KeyEventKind::Release && ev.code == binding.code && ev.modifiers == binding.modifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{key_match, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, Keybind};
+
+    #[test]
+    fn keybinds_keep_crossterm_format() {
+        let bind = Keybind::new(KeyCode::Char('q'), KeyModifiers::CONTROL);
+        let json = r#"{"code":{"Char":"q"},"modifiers":{"bits":2}}"#;
+        assert_eq!(serde_json::to_string(&bind).unwrap(), json);
+        assert_eq!(serde_json::from_str::<Keybind>(json).unwrap(), bind);
+    }
+
+    #[test]
+    fn releases_do_not_match() {
+        let bind = Keybind::new(KeyCode::Enter, KeyModifiers::NONE);
+        let mut ev = KeyEvent::from(&bind);
+        assert!(key_match(&ev, &bind));
+        ev.kind = KeyEventKind::Release;
+        assert!(!key_match(&ev, &bind));
+    }
+
+    #[test]
+    fn labels_fall_back_to_ascii() {
+        let bind = Keybind::new(KeyCode::Enter, KeyModifiers::SHIFT);
+        assert_eq!(bind.label(true), "\u{21e7}\u{23ce}");
+        assert_eq!(bind.label(false), "S-Enter");
+        let back_tab = Keybind::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert_eq!(back_tab.label(false), "S-Tab");
+    }
 }
@@ -0,0 +1,414 @@
+use std::{
+    collections::HashMap,
+    ops::{Bound, RangeBounds},
+};
+
+use ratatui::{
+    backend::Backend,
+    layout::{Alignment, Rect},
+    widgets::Paragraph,
+    Frame,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    rect::{Extent, Placement},
+    split::{Size, Split},
+};
+
+/// A declarative layout of named regions, built in code or deserialized
+/// from a config file. `solve` returns the area of every region.
+/// ```
+/// use ratatui::layout::Rect;
+/// use tui_utils::{layout::Node, split::Size};
+///
+/// let layout = Node::rows([
+///     (Size::Fixed(1), Node::region("header")),
+///     (
+///         Size::Fill(1),
+///         Node::columns([
+///             (Size::Fixed(20), Node::region("sidebar")),
+///             (Size::Fill(1), Node::region("main")),
+///         ]),
+///     ),
+/// ]);
+/// let regions = layout.solve(Rect::new(0, 0, 80, 24));
+/// assert_eq!(regions["main"], Rect::new(20, 1, 60, 23));
+/// ```
+///
+/// The same layout as JSON:
+/// ```json
+/// {"rows": {"children": [
+///     {"size": {"fixed": 1}, "region": "header"},
+///     {"size": {"fill": 1}, "columns": {"children": [
+///         {"size": {"fixed": 20}, "region": "sidebar"},
+///         {"size": {"fill": 1}, "region": "main"}
+///     ]}}
+/// ]}}
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Node {
+    /// An area named by the string
+    Region(String),
+    /// Children on top of each other
+    Rows(Group),
+    /// Children next to each other
+    Columns(Group),
+}
+
+/// Children of a `Node::Rows` or `Node::Columns`, split like a `Split`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Group {
+    #[serde(default)]
+    gap: u16,
+    #[serde(default)]
+    margin: u16,
+    children: Vec<Child>,
+}
+
+/// A node together with its size within the parent
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Child {
+    size: Size,
+    #[serde(flatten)]
+    node: Node,
+}
+
+impl Node {
+    pub fn region(name: impl Into<String>) -> Self {
+        Self::Region(name.into())
+    }
+
+    pub fn rows(children: impl IntoIterator<Item = (Size, Node)>) -> Self {
+        Self::Rows(Group::new(children))
+    }
+
+    pub fn columns(children: impl IntoIterator<Item = (Size, Node)>) -> Self {
+        Self::Columns(Group::new(children))
+    }
+
+    /// Empty cells between the children. Regions have no children, this
+    /// does nothing for them.
+    pub fn gap(mut self, gap: u16) -> Self {
+        if let Self::Rows(group) | Self::Columns(group) = &mut self {
+            group.gap = gap;
+        }
+        self
+    }
+
+    /// Empty cells around the children. Like `gap` this only applies to
+    /// rows and columns.
+    pub fn margin(mut self, margin: u16) -> Self {
+        if let Self::Rows(group) | Self::Columns(group) = &mut self {
+            group.margin = margin;
+        }
+        self
+    }
+
+    /// Compute the area of every region within `area`. Region names should
+    /// be unique, of regions sharing a name the last one wins.
+    pub fn solve(&self, area: Rect) -> HashMap<String, Rect> {
+        let mut regions = HashMap::new();
+        self.solve_into(area, &mut regions);
+        regions
+    }
+
+    fn solve_into(&self, area: Rect, regions: &mut HashMap<String, Rect>) {
+        let (group, split) = match self {
+            Self::Region(name) => {
+                regions.insert(name.clone(), area);
+                return;
+            }
+            Self::Rows(group) => (group, Split::rows(group.sizes())),
+            Self::Columns(group) => (group, Split::columns(group.sizes())),
+        };
+        let areas = split.gap(group.gap).margin(group.margin).split(area);
+        for (child, area) in group.children.iter().zip(areas.iter()) {
+            child.node.solve_into(*area, regions);
+        }
+    }
+}
+
+impl Group {
+    fn new(children: impl IntoIterator<Item = (Size, Node)>) -> Self {
+        Self {
+            children: children
+                .into_iter()
+                .map(|(size, node)| Child { size, node })
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    fn sizes(&self) -> impl Iterator<Item = Size> + '_ {
+        self.children.iter().map(|child| child.size)
+    }
+}
+
+/// Range of terminal sizes a layout of `Responsive` is meant for. Both
+/// ends are inclusive, by default every size matches. An empty range like
+/// `..0` matches no size.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct SizeRange {
+    min_width: u16,
+    max_width: u16,
+    min_height: u16,
+    max_height: u16,
+}
+
+impl Default for SizeRange {
+    fn default() -> Self {
+        Self {
+            min_width: 0,
+            max_width: u16::MAX,
+            min_height: 0,
+            max_height: u16::MAX,
+        }
+    }
+}
+
+impl SizeRange {
+    /// Widths within `range`, like `100..` or `60..100`
+    pub fn width(mut self, range: impl RangeBounds<u16>) -> Self {
+        (self.min_width, self.max_width) = inclusive(range);
+        self
+    }
+
+    /// Heights within `range`
+    pub fn height(mut self, range: impl RangeBounds<u16>) -> Self {
+        (self.min_height, self.max_height) = inclusive(range);
+        self
+    }
+
+    pub fn contains(&self, area: Rect) -> bool {
+        (self.min_width..=self.max_width).contains(&area.width)
+            && (self.min_height..=self.max_height).contains(&area.height)
+    }
+}
+
+/// The inclusive ends of `range`. Empty ranges get a minimum above their
+/// maximum, so they contain nothing.
+fn inclusive(range: impl RangeBounds<u16>) -> (u16, u16) {
+    let min = match range.start_bound() {
+        Bound::Included(&n) => Some(n),
+        Bound::Excluded(&n) => n.checked_add(1),
+        Bound::Unbounded => Some(0),
+    };
+    let max = match range.end_bound() {
+        Bound::Included(&n) => Some(n),
+        Bound::Excluded(&n) => n.checked_sub(1),
+        Bound::Unbounded => Some(u16::MAX),
+    };
+    min.zip(max).unwrap_or((1, 0))
+}
+
+/// A layout together with the sizes it is meant for
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Breakpoint<T> {
+    #[serde(flatten)]
+    range: SizeRange,
+    layout: T,
+}
+
+/// Picks one of several layouts by the size of the terminal, checked on
+/// every frame. Layouts are tried in the order they were added and the
+/// first one whose range matches wins. If none matches, or the terminal is
+/// smaller than `min_size`, the app should show `draw_too_small` instead.
+/// ```
+/// use ratatui::layout::Rect;
+/// use tui_utils::{
+///     layout::{Node, Responsive, SizeRange},
+///     split::Size,
+/// };
+///
+/// let sidebar = || (Size::Fixed(20), Node::region("sidebar"));
+/// let main = || (Size::Fill(1), Node::region("main"));
+/// let layouts = Responsive::new()
+///     .at(SizeRange::default().width(100..), Node::columns([sidebar(), main()]))
+///     .at(SizeRange::default().width(60..), Node::rows([sidebar(), main()]))
+///     // no sidebar below 60 columns
+///     .at(SizeRange::default(), Node::rows([main()]))
+///     .min_size(30, 10);
+///
+/// let regions = layouts.solve(Rect::new(0, 0, 50, 20)).unwrap();
+/// assert!(!regions.contains_key("sidebar"));
+/// assert!(layouts.solve(Rect::new(0, 0, 20, 20)).is_none());
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Responsive<T> {
+    breakpoints: Vec<Breakpoint<T>>,
+    #[serde(default)]
+    min_size: (u16, u16),
+}
+
+impl<T> Default for Responsive<T> {
+    fn default() -> Self {
+        Self {
+            breakpoints: Vec::new(),
+            min_size: (0, 0),
+        }
+    }
+}
+
+impl<T> Responsive<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `layout` for sizes within `range`, unless a layout added before
+    /// matches too
+    pub fn at(mut self, range: SizeRange, layout: T) -> Self {
+        self.breakpoints.push(Breakpoint { range, layout });
+        self
+    }
+
+    /// Below this size no layout is used at all
+    pub fn min_size(mut self, width: u16, height: u16) -> Self {
+        self.min_size = (width, height);
+        self
+    }
+
+    /// The layout for `area`, `None` if the terminal is too small
+    pub fn select(&self, area: Rect) -> Option<&T> {
+        if area.width < self.min_size.0 || area.height < self.min_size.1 {
+            return None;
+        }
+        self.breakpoints
+            .iter()
+            .find(|breakpoint| breakpoint.range.contains(area))
+            .map(|breakpoint| &breakpoint.layout)
+    }
+
+    /// Fill the frame with a message saying the terminal is too small,
+    /// along with the size it has and the size it needs
+    pub fn draw_too_small<B: Backend>(&self, f: &mut Frame<B>) {
+        let size = f.size();
+        let mut lines = vec![
+            String::from("Terminal too small"),
+            format!("{} x {}", size.width, size.height),
+        ];
+        if self.min_size != (0, 0) {
+            lines.push(format!("needs {} x {}", self.min_size.0, self.min_size.1));
+        }
+        let area =
+            Placement::new(Extent::Percent(100), Extent::Fixed(lines.len() as u16)).place(size);
+        let text = Paragraph::new(lines.join("\n")).alignment(Alignment::Center);
+        f.render_widget(text, area);
+    }
+}
+
+impl Responsive<Node> {
+    /// Solve the layout for `area`, `None` if the terminal is too small
+    pub fn solve(&self, area: Rect) -> Option<HashMap<String, Rect>> {
+        self.select(area).map(|node| node.solve(area))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use ratatui::{backend::TestBackend, buffer::Buffer, layout::Rect, Terminal};
+
+    use super::{Node, Responsive, SizeRange};
+    use crate::split::Size;
+
+    fn dashboard() -> Node {
+        Node::rows([
+            (Size::Fixed(3), Node::region("header")),
+            (
+                Size::Fill(1),
+                Node::columns([
+                    (Size::Percentage(25), Node::region("sidebar")),
+                    (Size::Fill(1), Node::region("main")),
+                ])
+                .gap(1),
+            ),
+            (Size::Fixed(1), Node::region("status")),
+        ])
+    }
+
+    #[test]
+    fn regions_are_solved_by_name() {
+        let regions = dashboard().solve(Rect::new(0, 0, 81, 30));
+        assert_eq!(regions.len(), 4);
+        assert_eq!(regions["header"], Rect::new(0, 0, 81, 3));
+        assert_eq!(regions["sidebar"], Rect::new(0, 3, 20, 26));
+        assert_eq!(regions["main"], Rect::new(21, 3, 60, 26));
+        assert_eq!(regions["status"], Rect::new(0, 29, 81, 1));
+    }
+
+    #[test]
+    fn layouts_deserialize() {
+        let json = r#"{"rows": {"children": [
+            {"size": {"fixed": 3}, "region": "header"},
+            {"size": {"fill": 1}, "columns": {"gap": 1, "children": [
+                {"size": {"percentage": 25}, "region": "sidebar"},
+                {"size": {"fill": 1}, "region": "main"}
+            ]}},
+            {"size": {"fixed": 1}, "region": "status"}
+        ]}}"#;
+        let layout: Node = serde_json::from_str(json).unwrap();
+        assert_eq!(layout, dashboard());
+    }
+
+    #[test]
+    fn first_matching_breakpoint_wins() {
+        let layouts = Responsive::new()
+            .at(SizeRange::default().width(100..), "wide")
+            .at(SizeRange::default().width(60..=99).height(..20), "short")
+            .at(SizeRange::default().width(60..), "narrow")
+            .min_size(40, 5);
+        assert_eq!(layouts.select(Rect::new(0, 0, 120, 30)), Some(&"wide"));
+        assert_eq!(layouts.select(Rect::new(0, 0, 80, 19)), Some(&"short"));
+        assert_eq!(layouts.select(Rect::new(0, 0, 80, 20)), Some(&"narrow"));
+        // matches nothing
+        assert_eq!(layouts.select(Rect::new(0, 0, 50, 20)), None);
+        // smaller than the minimum
+        assert_eq!(layouts.select(Rect::new(0, 0, 120, 4)), None);
+    }
+
+    #[test]
+    fn empty_ranges_match_nothing() {
+        let zero = Rect::new(0, 0, 0, 0);
+        assert!(!SizeRange::default().width(..0).contains(zero));
+        assert!(!SizeRange::default()
+            .height(5..5)
+            .contains(Rect::new(0, 0, 5, 5)));
+        let max = Rect::new(0, 0, u16::MAX, 1);
+        assert!(!SizeRange::default()
+            .width((Bound::Excluded(u16::MAX), Bound::Unbounded))
+            .contains(max));
+        assert!(SizeRange::default().width(..1).contains(zero));
+    }
+
+    #[test]
+    fn responsive_round_trips() {
+        let layouts = Responsive::new()
+            .at(SizeRange::default().width(100..), dashboard())
+            .at(SizeRange::default().height(..20), Node::region("main"))
+            .min_size(40, 5);
+        let json = serde_json::to_string(&layouts).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Responsive<Node>>(&json).unwrap(),
+            layouts
+        );
+    }
+
+    #[test]
+    fn too_small_screen_shows_sizes() {
+        let layouts = Responsive::<Node>::new().min_size(40, 10);
+        let mut terminal = Terminal::new(TestBackend::new(20, 5)).unwrap();
+        let frame = terminal.draw(|f| layouts.draw_too_small(f)).unwrap();
+        let expected = Buffer::with_lines(vec![
+            "                    ",
+            " Terminal too small ",
+            "       20 x 5       ",
+            "    needs 40 x 10   ",
+            "                    ",
+        ]);
+        assert_eq!(frame.buffer.content(), expected.content());
+    }
+}
@@ -1,13 +1,25 @@
 use std::time::Duration;
 
+#[cfg(feature = "tokio")]
+pub mod async_loop;
+pub mod backend;
 pub mod blocks;
+pub mod caps;
 pub mod component;
+pub mod event;
+pub mod event_loop;
 pub mod keys;
+pub mod layout;
+pub mod persist;
 pub mod rect;
+pub mod snapshot;
 pub mod split;
 pub mod state;
 pub mod style;
 pub mod term;
+pub mod testing;
+pub mod theme;
+pub mod tiling;
 
 pub mod shared {
     use std::rc::Rc;
@@ -23,7 +35,7 @@ pub mod shared {
     }
 }
 
-/// Used for event polling with crossterm
+/// Default tick rate and timeout for event polling
 pub(crate) static EVENT_TIMEOUT: Duration = Duration::from_millis(1000);
 /// Default highlight symbol
 pub static LIST_HIGHLIGHT_SYMBOL: &str = " > ";
@@ -0,0 +1,201 @@
+use std::{
+    env,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PersistError {
+    #[error("no state directory found in the environment")]
+    NoStateDir,
+    #[error("failed to access state file: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid state file: {0}")]
+    Format(#[from] serde_json::Error),
+}
+
+/// Saves and loads UI state like split ratios, collapsed panes and the
+/// chosen arrangement as JSON. State written by another `version` is
+/// ignored on load, so bump it whenever the saved type changes in a way
+/// older files would not deserialize into.
+/// ```no_run
+/// use tui_utils::{persist::StateFile, split::ResizableSplit};
+///
+/// let file = StateFile::new("my-app", 1)?;
+/// let mut split = ResizableSplit::columns(30);
+/// if let Some(state) = file.load()? {
+///     split.set_state(state);
+/// }
+/// // ... when the app exits
+/// file.save(&split.state())?;
+/// # Ok::<(), tui_utils::persist::PersistError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateFile {
+    path: PathBuf,
+    version: u32,
+}
+
+/// What is written to the file, the state along with its version
+#[derive(Serialize)]
+struct Versioned<'a, T> {
+    version: u32,
+    state: &'a T,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct Stored<T> {
+    state: T,
+}
+
+impl StateFile {
+    /// `layout.json` in the directory of `app` in the XDG state directory,
+    /// `$XDG_STATE_HOME` or `~/.local/state`. On Windows the local app
+    /// data directory is used instead.
+    pub fn new(app: &str, version: u32) -> Result<Self, PersistError> {
+        let dir = state_dir(|name| env::var_os(name)).ok_or(PersistError::NoStateDir)?;
+        Ok(Self::at(dir.join(app).join("layout.json"), version))
+    }
+
+    /// A state file at `path`
+    pub fn at(path: impl Into<PathBuf>, version: u32) -> Self {
+        Self {
+            path: path.into(),
+            version,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The saved state, `None` if nothing was saved yet or it was saved
+    /// by another version
+    pub fn load<T: DeserializeOwned>(&self) -> Result<Option<T>, PersistError> {
+        let json = match fs::read_to_string(&self.path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let header: Header = serde_json::from_str(&json)?;
+        if header.version != self.version {
+            return Ok(None);
+        }
+        let stored: Stored<T> = serde_json::from_str(&json)?;
+        Ok(Some(stored.state))
+    }
+
+    /// Save `state`, creating the directory if needed. The file is written
+    /// next to the old one and then renamed over it, so a crash never
+    /// leaves half a file behind.
+    pub fn save<T: Serialize>(&self, state: &T) -> Result<(), PersistError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(&Versioned {
+            version: self.version,
+            state,
+        })?;
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Base directory for state files, from the environment read by `var`
+fn state_dir(var: impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    let absolute = |name| var(name).map(PathBuf::from).filter(|p| p.is_absolute());
+    if cfg!(windows) {
+        return absolute("LOCALAPPDATA");
+    }
+    absolute("XDG_STATE_HOME").or_else(|| Some(absolute("HOME")?.join(".local/state")))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs,
+        path::{Path, PathBuf},
+    };
+
+    use super::{state_dir, StateFile};
+    use crate::split::{Pane, SplitState};
+
+    /// A directory for the files of one test, removed when dropped
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn temp_dir(name: &str) -> TempDir {
+        TempDir(env::temp_dir().join(format!("tui-utils-state-{}-{name}", std::process::id())))
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn state_dir_follows_xdg() {
+        let vars = |xdg: Option<&str>| {
+            state_dir(move |name| match name {
+                "XDG_STATE_HOME" => xdg.map(Into::into),
+                "HOME" => Some("/home/me".into()),
+                _ => None,
+            })
+        };
+        assert_eq!(vars(Some("/state")), Some(PathBuf::from("/state")));
+        assert_eq!(vars(None), Some(PathBuf::from("/home/me/.local/state")));
+        // relative paths are invalid per the spec
+        assert_eq!(
+            vars(Some("state")),
+            Some(PathBuf::from("/home/me/.local/state"))
+        );
+    }
+
+    #[test]
+    fn state_round_trips() {
+        let dir = temp_dir("round-trip");
+        let file = StateFile::at(dir.path().join("layout.json"), 1);
+        assert_eq!(file.load::<SplitState>().unwrap(), None);
+
+        let state = SplitState {
+            ratio: 300,
+            collapsed: Some(Pane::First),
+        };
+        file.save(&state).unwrap();
+        assert_eq!(file.load().unwrap(), Some(state));
+    }
+
+    #[test]
+    fn other_versions_are_ignored() {
+        let dir = temp_dir("versions");
+        let path = dir.path().join("versions.json");
+        let state = SplitState {
+            ratio: 500,
+            collapsed: None,
+        };
+        StateFile::at(&path, 1).save(&state).unwrap();
+        assert_eq!(StateFile::at(&path, 2).load::<SplitState>().unwrap(), None);
+
+        fs::write(&path, "{").unwrap();
+        assert!(StateFile::at(&path, 1).load::<SplitState>().is_err());
+    }
+}
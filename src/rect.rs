@@ -1,16 +1,568 @@
 use ratatui::layout::Rect;
 
-/// Produces a centered rect half the size of the width as well as the height.
+/// Produces a rect half the width and height of `size`, centered within it.
 pub fn centered_rect(size: Rect) -> Rect {
-    let width = size.width / 2;
-    let x = width / 2;
-    let height = size.height / 2;
-    let y = height / 2;
+    Placement::new(Extent::Percent(50), Extent::Percent(50)).place(size)
+}
+
+/// Geometry on `Rect`s. The math saturates, so tiny or empty rects give
+/// empty results instead of panicking, and so do rects reaching past the
+/// end of the `u16` range, which are cut off there first.
+/// ```
+/// use ratatui::layout::Rect;
+/// use tui_utils::rect::RectExt;
+///
+/// let area = Rect::new(0, 0, 80, 24).inset(1, 1);
+/// let (body, status) = area.split_bottom(1);
+/// assert_eq!(status, Rect::new(1, 22, 78, 1));
+/// assert!(body.contains_point(10, 5));
+/// ```
+pub trait RectExt {
+    /// Shrink by `horizontal` cells on the left and right and by `vertical`
+    /// cells at the top and bottom. Too small rects become empty, centered
+    /// where they were.
+    fn inset(self, horizontal: u16, vertical: u16) -> Rect;
+    /// The top `rows` rows and the rest below them
+    fn split_top(self, rows: u16) -> (Rect, Rect);
+    /// The rest above the bottom `rows` rows and those rows
+    fn split_bottom(self, rows: u16) -> (Rect, Rect);
+    /// The left `columns` columns and the rest to their right
+    fn split_left(self, columns: u16) -> (Rect, Rect);
+    /// The rest to the left of the right `columns` columns and those columns
+    fn split_right(self, columns: u16) -> (Rect, Rect);
+    /// The part within `other`. Rects which do not overlap give an empty
+    /// rect on the edge of `other` closest to this one, where
+    /// `Rect::intersection` would overflow.
+    fn intersect(self, other: Rect) -> Rect;
+    /// Whether the cell at `column` and `row` is within the rect, like a
+    /// mouse click on it
+    fn contains_point(self, column: u16, row: u16) -> bool;
+    /// Move the rect into `parent`, shrinking it only if it is larger
+    fn clamp_to(self, parent: Rect) -> Rect;
+    /// The cell at `column` and `row` of a grid of `columns` by `rows`
+    /// equally sized cells. The cells tile the rect, the ones further right
+    /// and down get the cells left over by rounding. Cells outside the grid
+    /// are empty.
+    fn grid_cell(self, columns: u16, rows: u16, column: u16, row: u16) -> Rect;
+}
+
+impl RectExt for Rect {
+    fn inset(self, horizontal: u16, vertical: u16) -> Rect {
+        let r = cut_off(self);
+        Rect {
+            x: r.x + horizontal.min(r.width / 2),
+            y: r.y + vertical.min(r.height / 2),
+            width: r.width.saturating_sub(horizontal.saturating_mul(2)),
+            height: r.height.saturating_sub(vertical.saturating_mul(2)),
+        }
+    }
+
+    fn split_top(self, rows: u16) -> (Rect, Rect) {
+        let r = cut_off(self);
+        let rows = rows.min(r.height);
+        (
+            Rect { height: rows, ..r },
+            Rect {
+                y: r.y + rows,
+                height: r.height - rows,
+                ..r
+            },
+        )
+    }
+
+    fn split_bottom(self, rows: u16) -> (Rect, Rect) {
+        let r = cut_off(self);
+        let (bottom, top) = r.split_top(r.height - rows.min(r.height));
+        (bottom, top)
+    }
+
+    fn split_left(self, columns: u16) -> (Rect, Rect) {
+        let r = cut_off(self);
+        let columns = columns.min(r.width);
+        (
+            Rect {
+                width: columns,
+                ..r
+            },
+            Rect {
+                x: r.x + columns,
+                width: r.width - columns,
+                ..r
+            },
+        )
+    }
+
+    fn split_right(self, columns: u16) -> (Rect, Rect) {
+        let r = cut_off(self);
+        r.split_left(r.width - columns.min(r.width))
+    }
+
+    fn intersect(self, other: Rect) -> Rect {
+        let (r, bounds) = (cut_off(self), cut_off(other));
+        let x = r.left().clamp(bounds.left(), bounds.right());
+        let y = r.top().clamp(bounds.top(), bounds.bottom());
+        let right = r.right().clamp(bounds.left(), bounds.right());
+        let bottom = r.bottom().clamp(bounds.top(), bounds.bottom());
+        Rect {
+            x,
+            y,
+            width: right.saturating_sub(x),
+            height: bottom.saturating_sub(y),
+        }
+    }
+
+    fn contains_point(self, column: u16, row: u16) -> bool {
+        (self.left()..self.right()).contains(&column) && (self.top()..self.bottom()).contains(&row)
+    }
+
+    fn clamp_to(self, parent: Rect) -> Rect {
+        let (r, parent) = (cut_off(self), cut_off(parent));
+        let width = r.width.min(parent.width);
+        let height = r.height.min(parent.height);
+        Rect {
+            x: slide(r.x, width, parent.left(), parent.right()),
+            y: slide(r.y, height, parent.top(), parent.bottom()),
+            width,
+            height,
+        }
+    }
+
+    fn grid_cell(self, columns: u16, rows: u16, column: u16, row: u16) -> Rect {
+        let r = cut_off(self);
+        if column >= columns || row >= rows {
+            return Rect {
+                width: 0,
+                height: 0,
+                ..r
+            };
+        }
+        // start of the `i`th of `n` parts of `length`
+        let edge = |length: u16, n: u16, i: u16| (length as u32 * i as u32 / n as u32) as u16;
+        let (x, y) = (edge(r.width, columns, column), edge(r.height, rows, row));
+        Rect {
+            x: r.x + x,
+            y: r.y + y,
+            width: edge(r.width, columns, column + 1) - x,
+            height: edge(r.height, rows, row + 1) - y,
+        }
+    }
+}
 
+/// `rect` without the part past the end of the `u16` range
+fn cut_off(rect: Rect) -> Rect {
     Rect {
-        x,
-        y,
-        width,
-        height,
+        width: rect.right() - rect.x,
+        height: rect.bottom() - rect.y,
+        ..rect
+    }
+}
+
+/// Width or height of a placed rect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Extent {
+    /// Percentage of the parent's width or height
+    Percent(u16),
+    /// Exactly this many cells
+    Fixed(u16),
+}
+
+impl Extent {
+    fn of(self, parent: u16) -> u16 {
+        match self {
+            Self::Percent(p) => (parent as u32 * p.min(100) as u32 / 100) as u16,
+            Self::Fixed(n) => n,
+        }
+    }
+}
+
+/// Where a placed rect goes within its parent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    #[default]
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+/// Side of another rect to place a rect on, see `Placement::place_beside`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Above,
+    Below,
+    Left,
+    Right,
+}
+
+/// Size and position of a popup, a dropdown or a tooltip.
+/// ```
+/// use ratatui::layout::Rect;
+/// use tui_utils::rect::{Anchor, Extent, Placement};
+///
+/// let popup = Placement::new(Extent::Percent(60), Extent::Fixed(5))
+///     .min_size(20, 3)
+///     .anchor(Anchor::Top)
+///     .place(Rect::new(10, 5, 50, 20));
+/// assert_eq!(popup, Rect::new(20, 5, 30, 5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    width: Extent,
+    height: Extent,
+    min: (u16, u16),
+    max: (u16, u16),
+    anchor: Anchor,
+}
+
+impl Placement {
+    pub fn new(width: Extent, height: Extent) -> Self {
+        Self {
+            width,
+            height,
+            min: (0, 0),
+            max: (u16::MAX, u16::MAX),
+            anchor: Anchor::default(),
+        }
+    }
+
+    /// Never smaller than this, unless the parent is
+    pub fn min_size(mut self, width: u16, height: u16) -> Self {
+        self.min = (width, height);
+        self
+    }
+
+    /// Never larger than this
+    pub fn max_size(mut self, width: u16, height: u16) -> Self {
+        self.max = (width, height);
+        self
+    }
+
+    /// Where `place` puts the rect within the parent. Centered by default.
+    pub fn anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// The size within `parent`, clamped to the minimum and maximum size
+    /// and to the parent itself
+    fn size(&self, parent: Rect) -> (u16, u16) {
+        let clamp =
+            |extent: Extent, of: u16, min: u16, max: u16| extent.of(of).min(max).max(min).min(of);
+        (
+            clamp(self.width, parent.width, self.min.0, self.max.0),
+            clamp(self.height, parent.height, self.min.1, self.max.1),
+        )
+    }
+
+    /// Place the rect within `parent` at the anchor
+    pub fn place(&self, parent: Rect) -> Rect {
+        let (width, height) = self.size(parent);
+        let (free_x, free_y) = (parent.width - width, parent.height - height);
+        let (x, y) = match self.anchor {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Top => (free_x / 2, 0),
+            Anchor::TopRight => (free_x, 0),
+            Anchor::Left => (0, free_y / 2),
+            Anchor::Center => (free_x / 2, free_y / 2),
+            Anchor::Right => (free_x, free_y / 2),
+            Anchor::BottomLeft => (0, free_y),
+            Anchor::Bottom => (free_x / 2, free_y),
+            Anchor::BottomRight => (free_x, free_y),
+        };
+        // `Rect::new` would shrink rects with an area above `u16::MAX`
+        Rect {
+            x: parent.x.saturating_add(x),
+            y: parent.y.saturating_add(y),
+            width,
+            height,
+        }
+    }
+
+    /// Place the rect next to `target` on `side`, aligned with its start,
+    /// like a dropdown below a field or a tooltip beside the cursor. If the
+    /// rect does not fit within `bounds` on that side it flips to the
+    /// opposite one, and if it fits on neither it takes the roomier one
+    /// and shrinks. It is moved along the side to stay within `bounds`.
+    /// Percentages are of `bounds`, the anchor is not used.
+    pub fn place_beside(&self, target: Rect, side: Side, bounds: Rect) -> Rect {
+        let (width, height) = self.size(bounds);
+        let target = target.intersect(bounds);
+        match side {
+            Side::Above | Side::Below => {
+                let above = target.top().saturating_sub(bounds.top());
+                let below = bounds.bottom().saturating_sub(target.bottom());
+                let (y, height) = beside(
+                    target.top(),
+                    target.bottom(),
+                    height,
+                    above,
+                    below,
+                    side == Side::Above,
+                );
+                Rect {
+                    x: slide(target.x, width, bounds.x, bounds.right()),
+                    y,
+                    width,
+                    height,
+                }
+            }
+            Side::Left | Side::Right => {
+                let left = target.left().saturating_sub(bounds.left());
+                let right = bounds.right().saturating_sub(target.right());
+                let (x, width) = beside(
+                    target.left(),
+                    target.right(),
+                    width,
+                    left,
+                    right,
+                    side == Side::Left,
+                );
+                Rect {
+                    x,
+                    y: slide(target.y, height, bounds.y, bounds.bottom()),
+                    width,
+                    height,
+                }
+            }
+        }
+    }
+}
+
+/// Position and length along the axis across the side, given the start and
+/// end of the target and the room before and after it
+fn beside(
+    start: u16,
+    end: u16,
+    length: u16,
+    before: u16,
+    after: u16,
+    prefer_before: bool,
+) -> (u16, u16) {
+    let (preferred, other) = match prefer_before {
+        true => (before, after),
+        false => (after, before),
+    };
+    let use_before = if length <= preferred {
+        prefer_before
+    } else if length <= other {
+        !prefer_before
+    } else {
+        before > after
+    };
+    match use_before {
+        true => {
+            let length = length.min(before);
+            (start - length, length)
+        }
+        false => (end, length.min(after)),
+    }
+}
+
+/// Start along the side, moved back so `length` cells stay within `min..max`
+fn slide(start: u16, length: u16, min: u16, max: u16) -> u16 {
+    start.min(max.saturating_sub(length)).max(min)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use ratatui::layout::Rect;
+
+    use super::{centered_rect, cut_off, Anchor, Extent, Placement, RectExt, Side};
+
+    #[test]
+    fn centered_rect_keeps_the_offset() {
+        assert_eq!(
+            centered_rect(Rect::new(0, 0, 80, 20)),
+            Rect::new(20, 5, 40, 10)
+        );
+        assert_eq!(
+            centered_rect(Rect::new(10, 4, 80, 20)),
+            Rect::new(30, 9, 40, 10)
+        );
+    }
+
+    #[test]
+    fn anchors_and_clamping() {
+        let parent = Rect::new(5, 5, 40, 10);
+        let placement = Placement::new(Extent::Fixed(60), Extent::Percent(10)).min_size(0, 3);
+        let corner = placement.anchor(Anchor::BottomRight).place(parent);
+        // clamped to the parent's width and up to the minimum height
+        assert_eq!(corner, Rect::new(5, 12, 40, 3));
+
+        let capped = Placement::new(Extent::Percent(100), Extent::Fixed(2))
+            .max_size(10, 2)
+            .anchor(Anchor::Right)
+            .place(parent);
+        assert_eq!(capped, Rect::new(35, 9, 10, 2));
+    }
+
+    #[test]
+    fn large_placements_are_not_shrunk() {
+        let parent = Rect {
+            x: 0,
+            y: 0,
+            width: 1000,
+            height: 500,
+        };
+        let full = Placement::new(Extent::Percent(100), Extent::Percent(100));
+        assert_eq!(full.place(parent), parent);
+
+        let below = full.place_beside(
+            Rect {
+                height: 1,
+                ..parent
+            },
+            Side::Below,
+            parent,
+        );
+        assert_eq!((below.width, below.height), (1000, 499));
+    }
+
+    #[test]
+    fn dropdown_flips_above_near_the_bottom() {
+        let bounds = Rect::new(0, 0, 80, 24);
+        let dropdown = Placement::new(Extent::Fixed(20), Extent::Fixed(8));
+        let field = Rect::new(10, 2, 30, 1);
+        assert_eq!(
+            dropdown.place_beside(field, Side::Below, bounds),
+            Rect::new(10, 3, 20, 8)
+        );
+
+        let field = Rect::new(70, 20, 10, 1);
+        // flipped above and moved left to stay within the bounds
+        assert_eq!(
+            dropdown.place_beside(field, Side::Below, bounds),
+            Rect::new(60, 12, 20, 8)
+        );
+    }
+
+    #[test]
+    fn targets_outside_the_bounds_do_not_panic() {
+        let bounds = Rect::new(0, 0, 30, 10);
+        let tooltip = Placement::new(Extent::Fixed(5), Extent::Fixed(1));
+        let outside = tooltip.place_beside(Rect::new(50, 40, 2, 2), Side::Below, bounds);
+        assert!(outside.height <= 1);
+    }
+
+    #[test]
+    fn tooltip_shrinks_when_nothing_fits() {
+        let bounds = Rect::new(0, 0, 30, 10);
+        let tooltip = Placement::new(Extent::Fixed(20), Extent::Fixed(1));
+        let cursor = Rect::new(12, 4, 1, 1);
+        // 12 cells to the left and 17 to the right
+        assert_eq!(
+            tooltip.place_beside(cursor, Side::Left, bounds),
+            Rect::new(13, 4, 17, 1)
+        );
+    }
+
+    fn any_rect() -> impl Strategy<Value = Rect> {
+        (any::<u16>(), any::<u16>(), any::<u16>(), any::<u16>()).prop_map(
+            |(x, y, width, height)| Rect {
+                x,
+                y,
+                width,
+                height,
+            },
+        )
+    }
+
+    fn small_rect() -> impl Strategy<Value = Rect> {
+        (0..50u16, 0..50u16, 0..50u16, 0..50u16)
+            .prop_map(|(x, y, width, height)| Rect::new(x, y, width, height))
+    }
+
+    fn within(inner: Rect, outer: Rect) -> bool {
+        inner.left() >= outer.left()
+            && inner.top() >= outer.top()
+            && inner.right() <= outer.right()
+            && inner.bottom() <= outer.bottom()
+    }
+
+    proptest! {
+        #[test]
+        fn insets_stay_inside(r in any_rect(), h in any::<u16>(), v in any::<u16>()) {
+            let inset = r.inset(h, v);
+            prop_assert!(within(inset, r));
+            prop_assert!(inset.width <= r.width && inset.height <= r.height);
+        }
+
+        #[test]
+        fn splits_cover_the_rect(r in any_rect(), n in any::<u16>()) {
+            let height = r.bottom() - r.y;
+            let width = r.right() - r.x;
+            for (a, b) in [r.split_top(n), r.split_bottom(n)] {
+                prop_assert!(within(a, r) && within(b, r));
+                prop_assert_eq!(a.bottom(), b.top());
+                prop_assert_eq!(a.height + b.height, height);
+            }
+            for (a, b) in [r.split_left(n), r.split_right(n)] {
+                prop_assert!(within(a, r) && within(b, r));
+                prop_assert_eq!(a.right(), b.left());
+                prop_assert_eq!(a.width + b.width, width);
+            }
+        }
+
+        #[test]
+        fn intersections_contain_shared_points(
+            a in small_rect(),
+            b in small_rect(),
+            column in 0..100u16,
+            row in 0..100u16,
+        ) {
+            let both = a.intersect(b);
+            prop_assert!(within(both, a) || both.area() == 0);
+            prop_assert!(within(both, b));
+            prop_assert_eq!(
+                both.contains_point(column, row),
+                a.contains_point(column, row) && b.contains_point(column, row)
+            );
+        }
+
+        #[test]
+        fn clamped_rects_fit_the_parent(r in any_rect(), parent in any_rect()) {
+            let clamped = r.clamp_to(parent);
+            prop_assert!(within(clamped, parent));
+            if within(r, parent) {
+                prop_assert_eq!(clamped, cut_off(r));
+            }
+        }
+
+        #[test]
+        fn placements_near_the_edge_stay_inside(
+            x in u16::MAX - 100.., y in u16::MAX - 100..,
+            width in any::<u16>(), height in any::<u16>(),
+            size in any::<(u16, u16)>(),
+            anchor in 0..9usize,
+        ) {
+            let parent = Rect { x, y, width, height };
+            let anchor = [
+                Anchor::TopLeft, Anchor::Top, Anchor::TopRight,
+                Anchor::Left, Anchor::Center, Anchor::Right,
+                Anchor::BottomLeft, Anchor::Bottom, Anchor::BottomRight,
+            ][anchor];
+            let placed = Placement::new(Extent::Fixed(size.0), Extent::Fixed(size.1))
+                .anchor(anchor)
+                .place(parent);
+            prop_assert!(within(placed, parent));
+        }
+
+        #[test]
+        fn grid_cells_tile_the_rect(r in small_rect(), columns in 1..10u16, rows in 1..10u16) {
+            let cells: Vec<Rect> = (0..rows)
+                .flat_map(|row| (0..columns).map(move |column| (column, row)))
+                .map(|(column, row)| r.grid_cell(columns, rows, column, row))
+                .collect();
+            prop_assert!(cells.iter().all(|&cell| within(cell, r)));
+            let area: u32 = cells.iter().map(|c| c.width as u32 * c.height as u32).sum();
+            prop_assert_eq!(area, r.width as u32 * r.height as u32);
+            prop_assert_eq!(r.grid_cell(columns, rows, columns, 0).area(), 0);
+        }
     }
 }
@@ -0,0 +1,358 @@
+use std::{env, fs, io, path::PathBuf};
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    layout::Rect,
+    style::{Color, Style},
+    widgets::Widget,
+};
+
+/// Set this environment variable to `1` to write new or changed snapshots
+/// to disk instead of failing the assertion.
+pub static UPDATE_ENV: &str = "TUI_UTILS_UPDATE_SNAPSHOTS";
+
+/// Render a widget into a buffer of the given size
+pub fn render_widget<W: Widget>(widget: W, width: u16, height: u16) -> Buffer {
+    let area = Rect::new(0, 0, width, height);
+    let mut buffer = Buffer::empty(area);
+    widget.render(area, &mut buffer);
+    buffer
+}
+
+/// Turn a buffer into its text representation, one line per row.
+/// With `styles` set, every row is followed by annotations describing
+/// the runs of non-default styles in it:
+/// ```text
+/// Hello world
+///   ^ 0..5 fg=Red +BOLD
+/// ```
+pub fn render_text(buffer: &Buffer, styles: bool) -> String {
+    let area = buffer.area();
+    let mut text = String::new();
+    for y in area.top()..area.bottom() {
+        let row: Vec<&Cell> = (area.left()..area.right())
+            .map(|x| buffer.get(x, y))
+            .collect();
+        text.extend(row.iter().map(|c| c.symbol.as_str()));
+        text.push('\n');
+        if styles {
+            for (start, end, style) in style_runs(&row) {
+                text.push_str(&format!("  ^ {start}..{end} {}\n", describe(style)));
+            }
+        }
+    }
+    text
+}
+
+fn style_runs(row: &[&Cell]) -> Vec<(usize, usize, Style)> {
+    let mut runs: Vec<(usize, usize, Style)> = vec![];
+    for (x, cell) in row.iter().enumerate() {
+        let style = cell.style();
+        match runs.last_mut() {
+            Some((_, end, s)) if *end == x && *s == style => *end += 1,
+            _ => runs.push((x, x + 1, style)),
+        }
+    }
+    runs.retain(|(_, _, style)| *style != Cell::default().style());
+    runs
+}
+
+fn describe(style: Style) -> String {
+    let default = Cell::default().style();
+    let mut parts = vec![];
+    if style.fg != default.fg {
+        parts.push(format!("fg={:?}", style.fg.unwrap_or(Color::Reset)));
+    }
+    if style.bg != default.bg {
+        parts.push(format!("bg={:?}", style.bg.unwrap_or(Color::Reset)));
+    }
+    if !style.add_modifier.is_empty() {
+        parts.push(format!("+{:?}", style.add_modifier));
+    }
+    parts.join(" ")
+}
+
+/// Stores rendered buffers as text files and compares later renders against them.
+/// ```no_run
+/// use ratatui::widgets::Paragraph;
+/// use tui_utils::snapshot::{self, Snapshot};
+///
+/// let buffer = snapshot::render_widget(Paragraph::new("hello"), 10, 1);
+/// Snapshot::default().assert("hello_paragraph", &buffer);
+/// ```
+pub struct Snapshot {
+    dir: PathBuf,
+    styles: bool,
+    update: bool,
+}
+
+impl Default for Snapshot {
+    /// Snapshots are stored in `tests/snapshots` of the crate under test and
+    /// updated when `TUI_UTILS_UPDATE_SNAPSHOTS=1` is set.
+    fn default() -> Self {
+        let root = env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        Self::new(root.join("tests").join("snapshots"))
+    }
+}
+
+impl Snapshot {
+    /// Store snapshots in `dir` instead of the default directory
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            dir: dir.into(),
+            styles: false,
+            update: env::var(UPDATE_ENV).is_ok_and(|v| v == "1"),
+        }
+    }
+
+    /// Include style annotations in the snapshot
+    pub fn with_styles(mut self, styles: bool) -> Self {
+        self.styles = styles;
+        self
+    }
+
+    /// Override whether mismatching snapshots are written to disk
+    pub fn with_update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// Path of the snapshot file with the given name
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.snap"))
+    }
+
+    /// Compare `buffer` against the stored snapshot, panicking with a
+    /// side-by-side diff if they differ.
+    pub fn assert(&self, name: &str, buffer: &Buffer) {
+        if let Err(message) = self.check(name, buffer) {
+            panic!("{message}");
+        }
+    }
+
+    /// Like `assert`, but returns the failure message instead of panicking
+    pub fn check(&self, name: &str, buffer: &Buffer) -> Result<(), String> {
+        let actual = render_text(buffer, self.styles);
+        let path = self.path(name);
+        let expected = match fs::read_to_string(&path) {
+            Ok(s) => Some(s),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("failed to read snapshot {}: {e}", path.display())),
+        };
+
+        if expected.as_deref() == Some(actual.as_str()) {
+            return Ok(());
+        }
+
+        if self.update {
+            return fs::create_dir_all(&self.dir)
+                .and_then(|_| fs::write(&path, &actual))
+                .map_err(|e| format!("failed to write snapshot {}: {e}", path.display()));
+        }
+
+        let hint = format!("rerun with {UPDATE_ENV}=1 to accept the new snapshot");
+        match expected {
+            Some(expected) => Err(format!(
+                "snapshot {} does not match, {hint}\n{}",
+                path.display(),
+                side_by_side(&expected, &actual)
+            )),
+            None => Err(format!(
+                "snapshot {} does not exist, {hint}\n{actual}",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// Produce a side-by-side line diff of two texts. Changed lines are
+/// marked with `|`, removed lines with `<` and added lines with `>`.
+pub fn side_by_side(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+    let width = old
+        .iter()
+        .map(|l| l.chars().count())
+        .max()
+        .unwrap_or_default()
+        .max("expected".len());
+
+    let mut out = format!("{:width$}   actual\n", "expected");
+    let mut pending_old: Vec<&str> = vec![];
+    let mut pending_new: Vec<&str> = vec![];
+    let flush = |out: &mut String, old: &mut Vec<&str>, new: &mut Vec<&str>| {
+        for i in 0..old.len().max(new.len()) {
+            let line = match (old.get(i), new.get(i)) {
+                (Some(o), Some(n)) => format!("{o:width$} | {n}"),
+                (Some(o), None) => format!("{o:width$} <"),
+                (None, Some(n)) => format!("{:width$} > {n}", ""),
+                (None, None) => unreachable!(),
+            };
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        old.clear();
+        new.clear();
+    };
+
+    for op in diff(&old, &new) {
+        match op {
+            DiffOp::Same(line) => {
+                flush(&mut out, &mut pending_old, &mut pending_new);
+                out.push_str(format!("{line:width$}   {line}").trim_end());
+                out.push('\n');
+            }
+            DiffOp::Removed(line) => pending_old.push(line),
+            DiffOp::Added(line) => pending_new.push(line),
+        }
+    }
+    flush(&mut out, &mut pending_old, &mut pending_new);
+    out
+}
+
+enum DiffOp<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Longest common subsequence diff, which is plenty for buffers the size of a terminal
+fn diff<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffOp<'a>> {
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut ops = vec![];
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            ops.push(DiffOp::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            ops.push(DiffOp::Removed(old[i]));
+            i += 1;
+        } else {
+            ops.push(DiffOp::Added(new[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[i..].iter().map(|l| DiffOp::Removed(l)));
+    ops.extend(new[j..].iter().map(|l| DiffOp::Added(l)));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs,
+        path::{Path, PathBuf},
+    };
+
+    use ratatui::{
+        style::{Color, Modifier, Style},
+        text::Span,
+        widgets::Paragraph,
+    };
+
+    use super::{render_text, render_widget, side_by_side, Snapshot};
+
+    /// A directory for the snapshots of one test, removed when dropped
+    struct SnapshotDir(PathBuf);
+
+    impl SnapshotDir {
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for SnapshotDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn snapshot_dir(test: &str) -> SnapshotDir {
+        let dir =
+            env::temp_dir().join(format!("tui-utils-snapshots-{}-{test}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        SnapshotDir(dir)
+    }
+
+    #[test]
+    fn plain_text() {
+        let buffer = render_widget(Paragraph::new("ab\ncd"), 3, 2);
+        assert_eq!(render_text(&buffer, false), "ab \ncd \n");
+    }
+
+    #[test]
+    fn style_annotations() {
+        let style = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+        let buffer = render_widget(Paragraph::new(Span::styled("ab", style)), 4, 1);
+        assert_eq!(render_text(&buffer, true), "ab  \n  ^ 0..2 fg=Red +BOLD\n");
+    }
+
+    #[test]
+    fn missing_snapshot_fails_without_update() {
+        let dir = snapshot_dir("missing");
+        let snapshot = Snapshot::new(dir.path()).with_update(false);
+        let buffer = render_widget(Paragraph::new("a"), 1, 1);
+        assert!(snapshot.check("missing", &buffer).is_err());
+    }
+
+    #[test]
+    fn update_writes_and_then_matches() {
+        let dir = snapshot_dir("update");
+        let buffer = render_widget(Paragraph::new("a"), 1, 1);
+
+        Snapshot::new(dir.path())
+            .with_update(true)
+            .assert("update", &buffer);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("update.snap")).unwrap(),
+            "a\n"
+        );
+
+        Snapshot::new(dir.path())
+            .with_update(false)
+            .assert("update", &buffer);
+    }
+
+    #[test]
+    fn mismatch_reports_diff() {
+        let dir = snapshot_dir("mismatch");
+        let first = render_widget(Paragraph::new("a"), 1, 1);
+        let second = render_widget(Paragraph::new("b"), 1, 1);
+
+        Snapshot::new(dir.path())
+            .with_update(true)
+            .assert("mismatch", &first);
+        let message = Snapshot::new(dir.path())
+            .with_update(false)
+            .check("mismatch", &second)
+            .unwrap_err();
+        assert!(message.contains("a        | b"));
+    }
+
+    #[test]
+    fn side_by_side_marks_changes() {
+        let diff = side_by_side("one\ntwo\nthree", "one\n2\nthree\nfour");
+        assert_eq!(
+            diff,
+            "expected   actual\n\
+             one        one\n\
+             two      | 2\n\
+             three      three\n\
+             \u{20}        > four\n"
+        );
+    }
+}
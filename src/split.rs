@@ -1,11 +1,41 @@
 use std::rc::Rc;
 
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::{
+    backend::Backend,
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    widgets::Widget,
+    Frame,
+};
+use serde::{Deserialize, Serialize};
 
-/// Set the percentage ratio for the split
-#[derive(Debug)]
+use crate::{
+    caps,
+    event::{MouseButton, MouseEvent, MouseEventKind},
+    keys::{key_match, KeyCode, KeyEvent, KeyModifiers, Keybind},
+    rect::RectExt,
+    theme::{self, Role},
+};
+
+/// Set the percentage ratio for the split. Serialized as a pair like
+/// `[70, 30]`, normalized when deserialized like `Ratio::new` does.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(from = "(u16, u16)", into = "(u16, u16)")]
 pub struct Ratio(u16, u16);
 
+impl From<(u16, u16)> for Ratio {
+    fn from((first, second): (u16, u16)) -> Self {
+        Self::new(first, second)
+    }
+}
+
+impl From<Ratio> for (u16, u16) {
+    fn from(ratio: Ratio) -> Self {
+        (ratio.0, ratio.1)
+    }
+}
+
 impl Default for Ratio {
     fn default() -> Self {
         Self(50, 50)
@@ -21,8 +51,8 @@ impl Ratio {
     /// let ratio = Ratio::new(70, 30);
     /// ```
     pub fn new(first: u16, second: u16) -> Self {
-        // no need to normalize the ratio
-        if first + second <= 100 {
+        // no need to normalize the ratio, summed wide so it cannot overflow
+        if first as u32 + second as u32 <= 100 {
             return Self(first, second);
         }
 
@@ -37,6 +67,11 @@ impl Ratio {
 
         Self(first as u16, second as u16)
     }
+
+    /// The ratio as sizes for a `Split`
+    pub fn sizes(&self) -> [Size; 2] {
+        [Size::Percentage(self.0), Size::Percentage(self.1)]
+    }
 }
 
 /// Generate a vertically split layout in a rect with a defined ratio
@@ -56,9 +91,568 @@ fn construct_split(re: Rect, ra: Ratio, d: Direction) -> Rc<[Rect]> {
         .split(re)
 }
 
+/// Size of a single pane in a `Split`. Serialized in snake case, like
+/// `{"fixed": 20}`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Size {
+    /// Percentage of the space left after gaps and margins. If the
+    /// percentages of a split add up to more than 100 they are normalized
+    /// to 100, like `Ratio::new` does.
+    Percentage(u16),
+    /// Exactly this many cells
+    Fixed(u16),
+    /// At least this many cells, growing like `Fill(1)`
+    Min(u16),
+    /// At most this many cells, growing like `Fill(1)`
+    Max(u16),
+    /// A share of the space nobody else takes, weighted by the value
+    Fill(u16),
+}
+
+/// Splits a rect into any number of panes side by side or stacked, with
+/// gaps between them and a margin around them.
+/// ```
+/// use ratatui::layout::Rect;
+/// use tui_utils::split::{Size, Split};
+///
+/// // a sidebar, a main pane and a preview taking a third of the rest
+/// let panes = Split::columns([Size::Fixed(20), Size::Fill(2), Size::Fill(1)])
+///     .gap(1)
+///     .split(Rect::new(0, 0, 83, 10));
+/// assert_eq!(panes[1], Rect::new(21, 0, 41, 10));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Split {
+    direction: Direction,
+    sizes: Vec<Size>,
+    gap: u16,
+    horizontal_margin: u16,
+    vertical_margin: u16,
+}
+
+impl Split {
+    /// Panes next to each other, from left to right
+    pub fn columns(sizes: impl IntoIterator<Item = Size>) -> Self {
+        Self::new(Direction::Horizontal, sizes)
+    }
+
+    /// Panes on top of each other, from top to bottom
+    pub fn rows(sizes: impl IntoIterator<Item = Size>) -> Self {
+        Self::new(Direction::Vertical, sizes)
+    }
+
+    fn new(direction: Direction, sizes: impl IntoIterator<Item = Size>) -> Self {
+        Self {
+            direction,
+            sizes: sizes.into_iter().collect(),
+            gap: 0,
+            horizontal_margin: 0,
+            vertical_margin: 0,
+        }
+    }
+
+    /// Empty cells between neighbouring panes
+    pub fn gap(mut self, gap: u16) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Empty cells around all panes
+    pub fn margin(mut self, margin: u16) -> Self {
+        self.horizontal_margin = margin;
+        self.vertical_margin = margin;
+        self
+    }
+
+    pub fn horizontal_margin(mut self, margin: u16) -> Self {
+        self.horizontal_margin = margin;
+        self
+    }
+
+    pub fn vertical_margin(mut self, margin: u16) -> Self {
+        self.vertical_margin = margin;
+        self
+    }
+
+    /// Compute the panes within `rect`. When the space is too small, panes
+    /// shrink from the last one backwards, percentages first and fixed
+    /// sizes last. Space left over when no pane can grow stays at the end.
+    pub fn split(&self, rect: Rect) -> Rc<[Rect]> {
+        let inner = Rect {
+            x: rect.x.saturating_add(self.horizontal_margin),
+            y: rect.y.saturating_add(self.vertical_margin),
+            width: rect
+                .width
+                .saturating_sub(self.horizontal_margin.saturating_mul(2)),
+            height: rect
+                .height
+                .saturating_sub(self.vertical_margin.saturating_mul(2)),
+        };
+        let extent = match self.direction {
+            Direction::Horizontal => inner.width,
+            Direction::Vertical => inner.height,
+        };
+        let gaps = self.gap as usize * self.sizes.len().saturating_sub(1);
+        let space = (extent as usize).saturating_sub(gaps);
+        let lengths = self.lengths(space);
+
+        let mut offset = 0;
+        lengths
+            .into_iter()
+            .map(|length| {
+                let pane = match self.direction {
+                    Direction::Horizontal => Rect {
+                        x: inner.x + offset.min(inner.width),
+                        width: length,
+                        ..inner
+                    },
+                    Direction::Vertical => Rect {
+                        y: inner.y + offset.min(inner.height),
+                        height: length,
+                        ..inner
+                    },
+                };
+                offset = offset.saturating_add(length).saturating_add(self.gap);
+                pane
+            })
+            .collect()
+    }
+
+    /// Lengths of the panes along the split direction within `space` cells
+    fn lengths(&self, space: usize) -> Vec<u16> {
+        let percentages = normalize(
+            self.sizes
+                .iter()
+                .filter_map(|size| match size {
+                    Size::Percentage(p) => Some(*p),
+                    _ => None,
+                })
+                .collect(),
+        );
+        // distributing the rest of 100 as well keeps rounding from losing cells
+        let mut weights: Vec<usize> = percentages.iter().map(|&p| p as usize).collect();
+        weights.push(100 - weights.iter().sum::<usize>());
+        let mut percentages = distribute(space, &weights).into_iter();
+        let mut lengths: Vec<usize> = self
+            .sizes
+            .iter()
+            .map(|size| match size {
+                Size::Percentage(_) => percentages.next().unwrap_or_default(),
+                Size::Fixed(n) | Size::Min(n) => *n as usize,
+                Size::Max(_) | Size::Fill(_) => 0,
+            })
+            .collect();
+
+        let used: usize = lengths.iter().sum();
+        if used > space {
+            self.shrink(&mut lengths, used - space);
+        } else {
+            self.grow(&mut lengths, space - used);
+        }
+        lengths.into_iter().map(|l| l as u16).collect()
+    }
+
+    fn shrink(&self, lengths: &mut [usize], mut excess: usize) {
+        let passes: [fn(&Size) -> bool; 3] = [
+            |size| matches!(size, Size::Percentage(_)),
+            |size| matches!(size, Size::Min(_)),
+            |size| matches!(size, Size::Fixed(_)),
+        ];
+        for shrinks in passes {
+            for (length, size) in lengths.iter_mut().zip(&self.sizes).rev() {
+                if excess == 0 {
+                    return;
+                }
+                if shrinks(size) {
+                    let cut = excess.min(*length);
+                    *length -= cut;
+                    excess -= cut;
+                }
+            }
+        }
+    }
+
+    /// Hand out `free` cells to the panes that can grow by their weight,
+    /// until they are all capped or the cells are gone
+    fn grow(&self, lengths: &mut [usize], mut free: usize) {
+        let mut weights: Vec<usize> = self
+            .sizes
+            .iter()
+            .map(|size| match size {
+                Size::Fill(weight) => *weight as usize,
+                Size::Min(_) | Size::Max(_) => 1,
+                Size::Percentage(_) | Size::Fixed(_) => 0,
+            })
+            .collect();
+        while free > 0 && weights.iter().any(|&w| w > 0) {
+            let shares = distribute(free, &weights);
+            for (i, share) in shares.into_iter().enumerate() {
+                let room = match self.sizes[i] {
+                    Size::Max(max) => (max as usize).saturating_sub(lengths[i]),
+                    _ => usize::MAX,
+                };
+                let share = share.min(room);
+                lengths[i] += share;
+                free -= share;
+                if room == share && matches!(self.sizes[i], Size::Max(_)) {
+                    weights[i] = 0;
+                }
+            }
+        }
+    }
+}
+
+/// One of the two panes of a `ResizableSplit`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Pane {
+    First,
+    Second,
+}
+
+/// What the user changed on a `ResizableSplit`, to be saved and restored
+/// on the next start, see `ResizableSplit::state`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitState {
+    /// Share of the first pane in permille
+    pub ratio: u16,
+    #[serde(default)]
+    pub collapsed: Option<Pane>,
+}
+
+/// Key bindings of a `ResizableSplit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitKeys {
+    /// Make the first pane larger
+    pub grow: Keybind,
+    /// Make the first pane smaller
+    pub shrink: Keybind,
+    /// Collapse the first pane, or restore it if it is collapsed
+    pub toggle_collapse: Keybind,
+}
+
+impl SplitKeys {
+    /// Alt with the arrow keys pointing along `direction`, and Alt+z
+    fn for_direction(direction: &Direction) -> Self {
+        let (grow, shrink) = match direction {
+            Direction::Horizontal => (KeyCode::Right, KeyCode::Left),
+            Direction::Vertical => (KeyCode::Down, KeyCode::Up),
+        };
+        Self {
+            grow: Keybind::new(grow, KeyModifiers::ALT),
+            shrink: Keybind::new(shrink, KeyModifiers::ALT),
+            toggle_collapse: Keybind::new(KeyCode::Char('z'), KeyModifiers::ALT),
+        }
+    }
+}
+
+/// Two panes with a divider between them which can be moved by dragging
+/// it with the mouse or with key bindings. The ratio is kept across
+/// frames and terminal resizes.
+/// ```
+/// use ratatui::layout::Rect;
+/// use tui_utils::split::ResizableSplit;
+///
+/// let mut split = ResizableSplit::columns(30).min_sizes(10, 20);
+/// let panes = split.split(Rect::new(0, 0, 81, 20));
+/// assert_eq!(panes[0].width, 24);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResizableSplit {
+    direction: Direction,
+    /// Share of the first pane in permille, for finer steps than percent
+    ratio: u16,
+    min_sizes: (u16, u16),
+    /// How far a key press moves the divider, in percent
+    step: u16,
+    keys: SplitKeys,
+    collapsed: Option<Pane>,
+    dragging: bool,
+    /// Area of the last `split`, used to map mouse positions
+    area: Rect,
+    divider: Rect,
+}
+
+impl ResizableSplit {
+    /// Panes next to each other, the first one taking `percent` of the width
+    pub fn columns(percent: u16) -> Self {
+        Self::new(Direction::Horizontal, percent)
+    }
+
+    /// Panes on top of each other, the first one taking `percent` of the height
+    pub fn rows(percent: u16) -> Self {
+        Self::new(Direction::Vertical, percent)
+    }
+
+    fn new(direction: Direction, percent: u16) -> Self {
+        Self {
+            keys: SplitKeys::for_direction(&direction),
+            direction,
+            ratio: percent.min(100) * 10,
+            min_sizes: (0, 0),
+            step: 5,
+            collapsed: None,
+            dragging: false,
+            area: Rect::default(),
+            divider: Rect::default(),
+        }
+    }
+
+    /// Sizes the panes never shrink below unless the area is too small
+    /// for both, in which case the first pane wins
+    pub fn min_sizes(mut self, first: u16, second: u16) -> Self {
+        self.min_sizes = (first, second);
+        self
+    }
+
+    /// Percent the divider moves per key press, 5 by default
+    pub fn step(mut self, percent: u16) -> Self {
+        self.step = percent;
+        self
+    }
+
+    pub fn keys(mut self, keys: SplitKeys) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Share of the first pane in percent
+    pub fn percent(&self) -> u16 {
+        self.ratio / 10
+    }
+
+    pub fn set_percent(&mut self, percent: u16) {
+        self.ratio = percent.min(100) * 10;
+    }
+
+    pub fn collapsed(&self) -> Option<Pane> {
+        self.collapsed
+    }
+
+    /// Hide `pane`, giving all the space to the other one. The ratio is
+    /// kept for when it is restored.
+    pub fn collapse(&mut self, pane: Pane) {
+        self.collapsed = Some(pane);
+        self.dragging = false;
+    }
+
+    pub fn restore(&mut self) {
+        self.collapsed = None;
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// The ratio and collapsed pane, for saving them across restarts
+    pub fn state(&self) -> SplitState {
+        SplitState {
+            ratio: self.ratio,
+            collapsed: self.collapsed,
+        }
+    }
+
+    /// Restore a saved `state`
+    pub fn set_state(&mut self, state: SplitState) {
+        self.ratio = state.ratio.min(1000);
+        self.collapsed = state.collapsed;
+        self.dragging = false;
+    }
+
+    /// Compute the two panes within `rect`, with a one cell divider between
+    /// them. A collapsed pane is empty and there is no divider.
+    pub fn split(&mut self, rect: Rect) -> Rc<[Rect]> {
+        self.area = rect;
+        let extent = self.extent(rect);
+        let (first, divider) = match self.collapsed {
+            Some(Pane::First) => (0, 0),
+            Some(Pane::Second) => (extent, 0),
+            None => (self.first_length(extent.saturating_sub(1)), extent.min(1)),
+        };
+        let second = extent - first - divider;
+
+        let [first, divider, second] = self.place(rect, [first, divider, second]);
+        self.divider = divider;
+        Rc::new([first, second])
+    }
+
+    fn extent(&self, rect: Rect) -> u16 {
+        match self.direction {
+            Direction::Horizontal => rect.width,
+            Direction::Vertical => rect.height,
+        }
+    }
+
+    /// Length of the first pane out of `space` cells, respecting the
+    /// minimum sizes
+    fn first_length(&self, space: u16) -> u16 {
+        let wanted = (space as u32 * self.ratio as u32 + 500) / 1000;
+        self.clamp_first(wanted as u16, space)
+    }
+
+    /// `wanted` cells for the first pane out of `space`, limited by the
+    /// minimum sizes
+    fn clamp_first(&self, wanted: u16, space: u16) -> u16 {
+        let max = space.saturating_sub(self.min_sizes.1);
+        wanted.min(max).max(self.min_sizes.0.min(space))
+    }
+
+    /// Lay out `lengths` one after another along the split direction
+    fn place(&self, rect: Rect, lengths: [u16; 3]) -> [Rect; 3] {
+        let mut offset = 0;
+        lengths.map(|length| {
+            let part = match self.direction {
+                Direction::Horizontal => Rect {
+                    x: rect.x + offset,
+                    width: length,
+                    ..rect
+                },
+                Direction::Vertical => Rect {
+                    y: rect.y + offset,
+                    height: length,
+                    ..rect
+                },
+            };
+            offset += length;
+            part
+        })
+    }
+
+    /// Draw the divider of the last `split`, highlighted while it is dragged
+    pub fn draw_divider<B: Backend>(&self, f: &mut Frame<B>) {
+        if self.divider.area() == 0 {
+            return;
+        }
+        let symbol = match (&self.direction, caps::current().unicode) {
+            (Direction::Horizontal, true) => "\u{2502}",
+            (Direction::Horizontal, false) => "|",
+            (Direction::Vertical, true) => "\u{2500}",
+            (Direction::Vertical, false) => "-",
+        };
+        let style = theme::style(match self.dragging {
+            true => Role::Accent,
+            false => Role::Dimmed,
+        });
+        f.render_widget(Divider { symbol, style }, self.divider);
+    }
+
+    /// Grow, shrink or collapse the first pane with the key bindings.
+    /// Returns whether the key was handled.
+    pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        let step = self.step.min(100) * 10;
+        if key_match(key, &self.keys.toggle_collapse) {
+            match self.collapsed {
+                Some(_) => self.restore(),
+                None => self.collapse(Pane::First),
+            }
+        } else if key_match(key, &self.keys.grow) && self.collapsed.is_none() {
+            self.ratio = (self.ratio + step).min(1000);
+        } else if key_match(key, &self.keys.shrink) && self.collapsed.is_none() {
+            self.ratio = self.ratio.saturating_sub(step);
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Start dragging on a left click on the divider, move it while the
+    /// button is held and stop when it is released. Returns whether the
+    /// event was handled.
+    pub fn handle_mouse(&mut self, mouse: &MouseEvent) -> bool {
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let on_divider = self.divider.contains_point(mouse.column, mouse.row);
+                self.dragging = on_divider;
+                on_divider
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.dragging => {
+                let (position, start) = match self.direction {
+                    Direction::Horizontal => (mouse.column, self.area.x),
+                    Direction::Vertical => (mouse.row, self.area.y),
+                };
+                let space = self.extent(self.area).saturating_sub(1);
+                if space > 0 {
+                    let first = position.saturating_sub(start).min(space);
+                    // snap to what the minimum sizes allow, so the divider
+                    // follows the mouse again as soon as it moves back
+                    self.ratio = permille(self.clamp_first(first, space), space);
+                }
+                true
+            }
+            MouseEventKind::Up(MouseButton::Left) if self.dragging => {
+                self.dragging = false;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Share of `length` in `space`, rounded, in permille
+fn permille(length: u16, space: u16) -> u16 {
+    ((length as u32 * 1000 + space as u32 / 2) / space as u32) as u16
+}
+
+/// Fills its area with the divider symbol
+struct Divider {
+    symbol: &'static str,
+    style: Style,
+}
+
+impl Widget for Divider {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                buf.get_mut(x, y)
+                    .set_symbol(self.symbol)
+                    .set_style(self.style);
+            }
+        }
+    }
+}
+
+/// Scale percentages adding up to more than 100 down to exactly 100
+fn normalize(percentages: Vec<u16>) -> Vec<u16> {
+    let sum: usize = percentages.iter().map(|&p| p as usize).sum();
+    if sum <= 100 {
+        return percentages;
+    }
+    let weights: Vec<usize> = percentages.iter().map(|&p| p as usize).collect();
+    distribute(100, &weights)
+        .into_iter()
+        .map(|p| p as u16)
+        .collect()
+}
+
+/// Split `total` by `weights`, giving the cells lost to rounding to the
+/// largest remainders so the shares add up to `total`
+fn distribute(total: usize, weights: &[usize]) -> Vec<usize> {
+    let sum: usize = weights.iter().sum();
+    if sum == 0 {
+        return vec![0; weights.len()];
+    }
+    let mut shares: Vec<usize> = weights.iter().map(|w| total * w / sum).collect();
+    let mut order: Vec<usize> = (0..weights.len()).collect();
+    // stable, so ties go to the earlier pane
+    order.sort_by_key(|&i| std::cmp::Reverse(total * weights[i] % sum));
+    let left = total - shares.iter().sum::<usize>();
+    for &i in order.iter().take(left) {
+        shares[i] += 1;
+    }
+    shares
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::split::Ratio;
+    use ratatui::layout::Rect;
+
+    use crate::{
+        event::{MouseButton, MouseEventKind},
+        keys::{KeyCode, KeyModifiers},
+        split::{Pane, Ratio, ResizableSplit, Size, Split, SplitState},
+        testing::{key_with, mouse},
+    };
 
     #[test]
     fn normalize_input() {
@@ -92,4 +686,131 @@ mod tests {
         assert_eq!(r.1, 20);
         assert!(r.0 + r.1 == 40);
     }
+
+    #[test]
+    fn mixed_sizes_fill_the_rect() {
+        let panes = Split::columns([Size::Fixed(10), Size::Percentage(50), Size::Fill(1)])
+            .split(Rect::new(0, 0, 100, 5));
+        assert_eq!(panes[0], Rect::new(0, 0, 10, 5));
+        assert_eq!(panes[1], Rect::new(10, 0, 50, 5));
+        assert_eq!(panes[2], Rect::new(60, 0, 40, 5));
+    }
+
+    #[test]
+    fn gaps_and_margins_are_left_empty() {
+        let panes = Split::rows([Size::Fill(1), Size::Fill(1), Size::Fill(1)])
+            .gap(1)
+            .margin(2)
+            .split(Rect::new(0, 0, 20, 15));
+        assert_eq!(panes[0], Rect::new(2, 2, 16, 3));
+        assert_eq!(panes[1], Rect::new(2, 6, 16, 3));
+        assert_eq!(panes[2], Rect::new(2, 10, 16, 3));
+    }
+
+    #[test]
+    fn percentages_over_100_are_normalized() {
+        let panes = Split::columns([Size::Percentage(150), Size::Percentage(75)])
+            .split(Rect::new(0, 0, 90, 1));
+        assert_eq!(panes[0].width + panes[1].width, 90);
+        assert_eq!(panes[0].width, 60);
+    }
+
+    #[test]
+    fn max_caps_growth_and_min_is_kept() {
+        let panes = Split::columns([Size::Max(5), Size::Min(10), Size::Fill(1)])
+            .split(Rect::new(0, 0, 40, 1));
+        let widths: Vec<u16> = panes.iter().map(|p| p.width).collect();
+        assert_eq!(widths, [5, 23, 12]);
+    }
+
+    #[test]
+    fn too_small_shrinks_from_the_back() {
+        let panes = Split::columns([Size::Fixed(10), Size::Fixed(10), Size::Percentage(50)])
+            .split(Rect::new(0, 0, 15, 1));
+        let widths: Vec<u16> = panes.iter().map(|p| p.width).collect();
+        assert_eq!(widths, [10, 5, 0]);
+        assert!(panes.iter().all(|p| p.right() <= 15));
+    }
+
+    #[test]
+    fn resizable_split_keeps_minimum_sizes() {
+        let mut split = ResizableSplit::columns(10).min_sizes(20, 30);
+        let panes = split.split(Rect::new(0, 0, 101, 10));
+        assert_eq!(panes[0], Rect::new(0, 0, 20, 10));
+        assert_eq!(panes[1], Rect::new(21, 0, 80, 10));
+
+        split.set_percent(90);
+        let panes = split.split(Rect::new(0, 0, 101, 10));
+        assert_eq!(panes[1].width, 30);
+    }
+
+    #[test]
+    fn keys_move_the_divider() {
+        let mut split = ResizableSplit::rows(50).step(10);
+        assert!(split.handle_key(&key_with(KeyCode::Down, KeyModifiers::ALT)));
+        assert_eq!(split.percent(), 60);
+        assert!(split.handle_key(&key_with(KeyCode::Up, KeyModifiers::ALT)));
+        assert!(split.handle_key(&key_with(KeyCode::Up, KeyModifiers::ALT)));
+        assert_eq!(split.percent(), 40);
+        assert!(!split.handle_key(&key_with(KeyCode::Up, KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn dragging_the_divider_resizes() {
+        let mut split = ResizableSplit::columns(50);
+        split.split(Rect::new(0, 0, 41, 5));
+        // the divider is at column 20
+        assert!(!split.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 5, 2)));
+        assert!(split.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 20, 2)));
+        assert!(split.handle_mouse(&mouse(MouseEventKind::Drag(MouseButton::Left), 10, 2)));
+        assert!(split.handle_mouse(&mouse(MouseEventKind::Up(MouseButton::Left), 10, 2)));
+        assert!(!split.is_dragging());
+
+        let panes = split.split(Rect::new(0, 0, 41, 5));
+        assert_eq!(panes[0].width, 10);
+        assert_eq!(panes[1], Rect::new(11, 0, 30, 5));
+    }
+
+    #[test]
+    fn collapse_and_restore() {
+        let mut split = ResizableSplit::columns(30);
+        split.collapse(Pane::First);
+        let panes = split.split(Rect::new(0, 0, 50, 5));
+        assert_eq!(panes[0].width, 0);
+        assert_eq!(panes[1], Rect::new(0, 0, 50, 5));
+
+        assert!(split.handle_key(&key_with(KeyCode::Char('z'), KeyModifiers::ALT)));
+        assert_eq!(split.collapsed(), None);
+        assert_eq!(split.split(Rect::new(0, 0, 51, 5))[0].width, 15);
+    }
+
+    #[test]
+    fn ratios_and_split_state_serialize() {
+        let ratio: Ratio = serde_json::from_str("[150, 75]").unwrap();
+        assert_eq!(ratio, Ratio::new(150, 75));
+        let ratio: Ratio = serde_json::from_str("[60000, 60000]").unwrap();
+        assert_eq!(ratio.0 + ratio.1, 100);
+        assert_eq!(
+            serde_json::to_string(&Ratio::new(70, 30)).unwrap(),
+            "[70,30]"
+        );
+
+        let mut split = ResizableSplit::columns(35);
+        split.collapse(Pane::Second);
+        let json = serde_json::to_string(&split.state()).unwrap();
+        assert_eq!(json, r#"{"ratio":350,"collapsed":"second"}"#);
+
+        let mut restored = ResizableSplit::columns(50);
+        restored.set_state(serde_json::from_str(&json).unwrap());
+        assert_eq!(restored.percent(), 35);
+        assert_eq!(restored.collapsed(), Some(Pane::Second));
+        restored.set_state(serde_json::from_str(r#"{"ratio":2000}"#).unwrap());
+        assert_eq!(
+            restored.state(),
+            SplitState {
+                ratio: 1000,
+                collapsed: None
+            }
+        );
+    }
 }
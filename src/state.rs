@@ -45,6 +45,7 @@ pub enum StateWrap {
     Disable,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for StateWrap {
     fn default() -> Self {
         Self::Enable
@@ -290,6 +291,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::int_plus_one)]
     fn decrease_bounds_size_should_correct_oob() {
         let mut v = vec![1, 2, 3, 4, 5, 6];
         let boundary = Boundary::from(&v);
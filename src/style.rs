@@ -1,8 +1,10 @@
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::Style;
 
-/// Simple highlight style to be used with stateful widgets
+use crate::theme::{self, Role};
+
+/// Simple highlight style to be used with stateful widgets, the highlight
+/// of the active theme. Without colors the highlighted item is reversed
+/// instead.
 pub fn highlight_style() -> Style {
-    Style::default()
-        .bg(Color::Indexed(8))
-        .add_modifier(Modifier::BOLD)
+    theme::style(Role::Highlight)
 }
@@ -1,12 +1,19 @@
+use crate::backend::{self, DefaultBackend, DefaultInput, EventInput, Restorer, TermBackend};
+use crate::caps;
+use crate::event::Event;
 use crate::EVENT_TIMEOUT;
-use crossterm::event::{self, Event};
-use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
-};
-use ratatui::backend::CrosstermBackend;
-use ratatui::Terminal;
-use std::error::Error;
-use std::io;
+use bitflags::bitflags;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::{Frame, Terminal, TerminalOptions, Viewport};
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::ops::{Deref, DerefMut};
+use std::panic;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Once};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -17,53 +24,935 @@ pub enum TermError {
     Restore(String),
     #[error("failed to init terminal: {0}")]
     Init(String),
+    #[error("failed to suspend: {0}")]
+    Suspend(String),
 }
 
-/// Prepare the terminal by clearing the screen and hiding the cursor
-pub fn init() -> Result<Terminal<CrosstermBackend<io::Stdout>>, TermError> {
-    crossterm::execute!(io::stdout(), EnterAlternateScreen)
-        .map_err(|e| TermError::Init(e.to_string()))?;
-    enable_raw_mode().map_err(|e| TermError::Init(e.to_string()))?;
+/// Describes what `init` sets up on the terminal. Everything enabled here
+/// is disabled again when the terminal is restored.
+/// ```no_run
+/// use tui_utils::term::InitOptions;
+///
+/// let terminal = InitOptions::default()
+///     .mouse_capture(true)
+///     .bracketed_paste(true)
+///     .init()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct InitOptions {
+    alternate_screen: bool,
+    raw_mode: bool,
+    mouse_capture: bool,
+    bracketed_paste: bool,
+    focus_change: bool,
+    keyboard_enhancement: Option<KeyboardEnhancementFlags>,
+    show_cursor: bool,
+    inline: Option<u16>,
+    inline_exit: InlineExit,
+    /// Filled in during setup with the lines that were reserved
+    inline_area: Option<Rect>,
+    output: Output,
+    /// `None` leaves it to `caps::current`
+    synchronized_output: Option<bool>,
+}
+
+/// Where `init` draws the terminal. Input is read from the terminal itself
+/// on every backend, so drawing to stderr or the tty keeps stdout free
+/// for data, e.g. in `cmd | picker | xargs`. termion measures the terminal
+/// through stdout, so with termion stdout still has to be a terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Output {
+    #[default]
+    Stdout,
+    Stderr,
+    /// Open the controlling terminal directly
+    Tty,
+}
+
+#[cfg(not(windows))]
+const TTY: &str = "/dev/tty";
+#[cfg(windows)]
+const TTY: &str = "CONOUT$";
+
+impl Output {
+    pub fn open(self) -> io::Result<OutputWriter> {
+        Ok(match self {
+            Self::Stdout => OutputWriter::Stdout(io::stdout()),
+            Self::Stderr => OutputWriter::Stderr(BufWriter::new(io::stderr())),
+            Self::Tty => {
+                OutputWriter::Tty(BufWriter::new(OpenOptions::new().write(true).open(TTY)?))
+            }
+        })
+    }
+}
+
+/// Writer for an `Output`. Unlike stdout, stderr and the tty are not
+/// buffered by default, so they are wrapped in a `BufWriter` to keep a
+/// frame from being written in many small pieces.
+#[derive(Debug)]
+pub enum OutputWriter {
+    Stdout(io::Stdout),
+    Stderr(BufWriter<io::Stderr>),
+    Tty(BufWriter<File>),
+}
+
+impl Write for OutputWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Stdout(w) => w.write(buf),
+            Self::Stderr(w) => w.write(buf),
+            Self::Tty(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Stdout(w) => w.flush(),
+            Self::Stderr(w) => w.flush(),
+            Self::Tty(w) => w.flush(),
+        }
+    }
+}
+
+/// Shared count of the bytes written through a `CountingWriter`
+#[derive(Debug, Clone, Default)]
+pub struct ByteCounter(Arc<AtomicU64>);
+
+impl ByteCounter {
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Writer counting the bytes written through it. Pass its counter to
+/// `TerminalGuard::count_bytes` to get the bytes written per frame.
+#[derive(Debug)]
+pub struct CountingWriter<W> {
+    inner: W,
+    counter: ByteCounter,
+}
+
+impl<W: Write> CountingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            counter: ByteCounter::default(),
+        }
+    }
+
+    pub fn counter(&self) -> ByteCounter {
+        self.counter.clone()
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.counter.0.fetch_add(written as u64, Ordering::Relaxed);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+bitflags! {
+    /// Flags of the kitty keyboard protocol, see `InitOptions::keyboard_enhancement`
+    pub struct KeyboardEnhancementFlags: u8 {
+        const DISAMBIGUATE_ESCAPE_CODES = 0b0000_0001;
+        const REPORT_EVENT_TYPES = 0b0000_0010;
+        const REPORT_ALTERNATE_KEYS = 0b0000_0100;
+        const REPORT_ALL_KEYS_AS_ESCAPE_CODES = 0b0000_1000;
+        const REPORT_ASSOCIATED_TEXT = 0b0001_0000;
+    }
+}
+
+/// What happens to an inline viewport when the terminal is restored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InlineExit {
+    /// Leave the last frame in the scrollback and continue below it
+    #[default]
+    Keep,
+    /// Clear the viewport and continue where it started
+    Clear,
+}
+
+impl Default for InitOptions {
+    /// Raw mode on the alternate screen with a hidden cursor, which is what
+    /// `init` does.
+    fn default() -> Self {
+        Self {
+            alternate_screen: true,
+            raw_mode: true,
+            mouse_capture: false,
+            bracketed_paste: false,
+            focus_change: false,
+            keyboard_enhancement: None,
+            show_cursor: false,
+            inline: None,
+            inline_exit: InlineExit::default(),
+            inline_area: None,
+            output: Output::default(),
+            synchronized_output: None,
+        }
+    }
+}
+
+impl InitOptions {
+    /// Options with nothing enabled, used to keep track of what has been set up so far
+    pub(crate) fn none() -> Self {
+        Self {
+            alternate_screen: false,
+            raw_mode: false,
+            show_cursor: true,
+            ..Self::default()
+        }
+    }
+
+    /// Render on the alternate screen. Enabled by default.
+    pub fn alternate_screen(mut self, enable: bool) -> Self {
+        self.alternate_screen = enable;
+        self
+    }
+
+    /// Report mouse events
+    pub fn mouse_capture(mut self, enable: bool) -> Self {
+        self.mouse_capture = enable;
+        self
+    }
+
+    /// Report pasted text as a single `Event::Paste` instead of key presses
+    pub fn bracketed_paste(mut self, enable: bool) -> Self {
+        self.bracketed_paste = enable;
+        self
+    }
+
+    /// Report when the terminal gains or loses focus
+    pub fn focus_change(mut self, enable: bool) -> Self {
+        self.focus_change = enable;
+        self
+    }
+
+    /// Enable the kitty keyboard protocol with the given flags, which allows
+    /// telling apart key releases, repeats and more modifier combinations.
+    /// Terminals without support for the protocol ignore this.
+    pub fn keyboard_enhancement(mut self, flags: KeyboardEnhancementFlags) -> Self {
+        self.keyboard_enhancement = Some(flags);
+        self
+    }
+
+    /// Render into `height` lines at the current cursor position instead of
+    /// taking over the screen, which is useful for short prompts. This
+    /// skips the alternate screen. What happens to the rendered lines on
+    /// exit is set with `inline_exit`.
+    pub fn inline(mut self, height: u16) -> Self {
+        self.inline = Some(height);
+        self
+    }
+
+    /// Whether an inline viewport is kept in the scrollback or cleared on exit
+    pub fn inline_exit(mut self, exit: InlineExit) -> Self {
+        self.inline_exit = exit;
+        self
+    }
 
-    let backend = CrosstermBackend::new(io::stdout());
+    /// Keep the cursor visible. It is hidden by default.
+    pub fn show_cursor(mut self, show: bool) -> Self {
+        self.show_cursor = show;
+        self
+    }
 
-    let mut terminal = Terminal::new(backend).map_err(|e| TermError::Init(e.to_string()))?;
-    terminal
-        .hide_cursor()
-        .map_err(|e| TermError::Init(e.to_string()))?;
+    /// Draw to `output` instead of stdout. The panic hook restores the
+    /// terminal on `output` too, so nothing is written to stdout.
+    pub fn output(mut self, output: Output) -> Self {
+        self.output = output;
+        self
+    }
 
-    Ok(terminal)
+    /// Wrap each draw in synchronized update sequences, so the terminal
+    /// shows the whole frame at once instead of flickering through a large
+    /// redraw. By default this is done if `caps::current` reports support.
+    pub fn synchronized_output(mut self, enable: bool) -> Self {
+        self.synchronized_output = Some(enable);
+        self
+    }
+
+    /// Prepare the terminal on the chosen output with the default backend.
+    /// Like `init`, this installs the panic hook.
+    pub fn init(self) -> Result<TerminalGuard, TermError> {
+        let (backend, counter) =
+            backend::default_backend(self.output).map_err(|e| TermError::Init(e.to_string()))?;
+        let guard = self.init_with(backend)?;
+        Ok(match counter {
+            Some(counter) => guard.count_bytes(counter),
+            None => guard,
+        })
+    }
+
+    /// Prepare the terminal drawn on by `backend`, for example a
+    /// `TermionBackend` when more than one backend feature is enabled.
+    /// The backend should draw to the chosen `output`, which the panic hook
+    /// restores. Like `init`, this installs the panic hook.
+    pub fn init_with<B: TermBackend>(self, backend: B) -> Result<TerminalGuard<B>, TermError> {
+        install_panic_hook();
+        let mut guard = TerminalGuard::new(backend, self)?;
+        guard.track();
+        Ok(guard)
+    }
+
+    /// Undo everything these options enabled, in reverse order
+    pub(crate) fn undo<B: TermBackend>(&self, backend: &mut B) -> io::Result<()> {
+        if self.keyboard_enhancement.is_some() {
+            backend.pop_keyboard_enhancement()?;
+        }
+        if self.focus_change {
+            backend.set_focus_change(false)?;
+        }
+        if self.bracketed_paste {
+            backend.set_bracketed_paste(false)?;
+        }
+        if self.mouse_capture {
+            backend.set_mouse_capture(false)?;
+        }
+        if self.raw_mode {
+            backend.set_raw_mode(false)?;
+        }
+        if self.alternate_screen {
+            backend.set_alternate_screen(false)?;
+        }
+        if let Some(area) = self.inline_area {
+            match self.inline_exit {
+                InlineExit::Keep => {
+                    // a line feed on the last line scrolls if the viewport is at the bottom
+                    backend.set_cursor(0, area.bottom().saturating_sub(1))?;
+                    backend.write_sequence("\r\n")?;
+                }
+                InlineExit::Clear => {
+                    backend.set_cursor(0, area.y)?;
+                    backend.clear_below_cursor()?;
+                }
+            }
+        }
+        if !self.show_cursor {
+            backend.show_cursor()?;
+        }
+        backend.flush()
+    }
 }
 
-// Restore the terminal and print an error
-pub fn restore_with_err(e: Box<dyn Error>) -> Result<(), TermError> {
+/// Options of the terminal set up through `init` which have not been
+/// restored yet, together with a way to undo them without the guard. The
+/// panic hook and `restore` use this to know what to undo.
+fn active() -> MutexGuard<'static, Option<(InitOptions, Restorer)>> {
+    static ACTIVE: Mutex<Option<(InitOptions, Restorer)>> = Mutex::new(None);
+    ACTIVE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// What it took to draw a frame, returned by `TerminalGuard::draw`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameStats {
+    /// Cells which differed from the previous frame and were redrawn
+    pub cells_changed: usize,
+    /// Bytes sent to the terminal, if they are counted. `init` counts them,
+    /// see `TerminalGuard::count_bytes` for other backends.
+    pub bytes_written: Option<u64>,
+    pub duration: Duration,
+}
+
+/// Count the cells `frame` changed since `last` and update `last` to it.
+/// ratatui resets its previous buffer after each draw, so a copy is kept,
+/// but only changed cells are copied to avoid cloning the whole buffer on
+/// every frame.
+fn count_changes(last: &mut Option<Buffer>, frame: &Buffer) -> usize {
+    match last {
+        Some(last) if last.area == frame.area => {
+            let changed = last.diff(frame).len();
+            for (old, new) in last.content.iter_mut().zip(&frame.content) {
+                if old != new {
+                    *old = new.clone();
+                }
+            }
+            changed
+        }
+        // a new area means ratatui redrew from a cleared screen
+        _ => {
+            *last = Some(frame.clone());
+            Buffer::empty(frame.area).diff(frame).len()
+        }
+    }
+}
+
+/// Owns the terminal prepared by `init` and restores it when dropped, so an
+/// early return or a panic never leaves the terminal in raw mode on the
+/// alternate screen. Derefs to the inner `Terminal`, but draws through its
+/// own `draw` which adds synchronized output and frame statistics.
+pub struct TerminalGuard<B: TermBackend = DefaultBackend> {
+    terminal: Terminal<B>,
+    /// What has actually been enabled, which is what gets undone on restore
+    enabled: InitOptions,
+    /// What was asked for, which is set up again when resuming
+    options: InitOptions,
+    /// Whether this guard is the one tracked by `active`
+    global: bool,
+    /// The last frame drawn, to count the cells changed by the next one
+    last_frame: Option<Buffer>,
+    last_stats: Option<FrameStats>,
+    bytes: Option<ByteCounter>,
+}
+
+impl<B: TermBackend> TerminalGuard<B> {
+    /// Set up the terminal drawn on by `backend` as described by `options`.
+    /// If any step fails, the steps before it are undone.
+    pub(crate) fn new(mut backend: B, options: InitOptions) -> Result<Self, TermError> {
+        let mut enabled = InitOptions::none();
+        let viewport = match setup(&mut backend, &options, &mut enabled) {
+            Ok(viewport) => viewport,
+            Err(e) => {
+                let _ = enabled.undo(&mut backend);
+                return Err(TermError::Init(e.to_string()));
+            }
+        };
+
+        let terminal = match viewport {
+            Some(area) => Terminal::with_options(
+                backend,
+                TerminalOptions {
+                    viewport: Viewport::fixed(area),
+                },
+            ),
+            // `setup` checked that the size can be queried, which is the only
+            // way creating the terminal can fail
+            None => Terminal::new(backend),
+        };
+        let mut guard = Self {
+            terminal: terminal.map_err(|e| TermError::Init(e.to_string()))?,
+            enabled,
+            options,
+            global: false,
+            last_frame: None,
+            last_stats: None,
+            bytes: None,
+        };
+        guard.hide_cursor()?;
+        Ok(guard)
+    }
+
+    /// Report the bytes counted by `counter`, which should count what is
+    /// written by the backend, in the stats of each frame
+    pub fn count_bytes(mut self, counter: ByteCounter) -> Self {
+        self.bytes = Some(counter);
+        self
+    }
+
+    /// Draw a frame like `Terminal::draw`, wrapped in synchronized update
+    /// sequences if enabled
+    pub fn draw<F>(&mut self, f: F) -> io::Result<FrameStats>
+    where
+        F: FnOnce(&mut Frame<B>),
+    {
+        let start = Instant::now();
+        let bytes_before = self.bytes.as_ref().map(ByteCounter::get);
+        let sync = self
+            .options
+            .synchronized_output
+            .unwrap_or_else(|| caps::current().synchronized_output);
+
+        if sync {
+            self.terminal.backend_mut().write_sequence("\x1b[?2026h")?;
+        }
+        let drawn = self
+            .terminal
+            .draw(f)
+            .map(|frame| count_changes(&mut self.last_frame, frame.buffer));
+        // end the update even if drawing failed, the terminal would stop
+        // rendering otherwise
+        let ended = match sync {
+            true => self.terminal.backend_mut().write_sequence("\x1b[?2026l"),
+            false => Ok(()),
+        };
+        let cells_changed = drawn?;
+        ended?;
+
+        let bytes_written = self.bytes.as_ref().map(ByteCounter::get);
+        let stats = FrameStats {
+            cells_changed,
+            bytes_written: bytes_written.zip(bytes_before).map(|(a, b)| a - b),
+            duration: start.elapsed(),
+        };
+        self.last_stats = Some(stats);
+        Ok(stats)
+    }
+
+    /// Stats of the last frame drawn through `draw`
+    pub fn last_frame_stats(&self) -> Option<FrameStats> {
+        self.last_stats
+    }
+
+    /// Make this the guard undone by the panic hook and `restore`, if the
+    /// backend can be restored from there
+    fn track(&mut self) {
+        if let Some(restorer) = self.terminal.backend().restorer(self.options.output) {
+            self.global = true;
+            *active() = Some((self.enabled.clone(), restorer));
+        }
+    }
+
+    fn hide_cursor(&mut self) -> Result<(), TermError> {
+        if !self.options.show_cursor {
+            self.terminal
+                .hide_cursor()
+                .map_err(|e| TermError::Init(e.to_string()))?;
+            self.enabled.show_cursor = false;
+        }
+        Ok(())
+    }
+
+    /// Suspend the application like a shell would on Ctrl+Z. The terminal is
+    /// restored, the process is stopped with `SIGTSTP` and once it receives
+    /// `SIGCONT` (e.g. through `fg`) the terminal is set up again and fully
+    /// redrawn on the next draw. In inline mode new lines are reserved at the
+    /// cursor and the screen is cleared. This does nothing on non-unix platforms.
+    pub fn suspend(&mut self) -> Result<(), TermError> {
+        #[cfg(unix)]
+        {
+            self.restore()?;
+            signal_hook::low_level::raise(signal_hook::consts::SIGTSTP)
+                .map_err(|e| TermError::Suspend(e.to_string()))?;
+            // execution continues here after SIGCONT
+            self.resume()?;
+        }
+        Ok(())
+    }
+
+    /// Set the terminal up again after it was restored and force a full redraw
+    fn resume(&mut self) -> Result<(), TermError> {
+        let init_err = |e: io::Error| TermError::Init(e.to_string());
+        // record progress on the guard so dropping it undoes a partial setup
+        let viewport = setup(
+            self.terminal.backend_mut(),
+            &self.options,
+            &mut self.enabled,
+        )
+        .map_err(init_err)?;
+        match viewport {
+            Some(area) => self.terminal.resize(area).map_err(init_err)?,
+            None => self.terminal.clear().map_err(init_err)?,
+        }
+        self.last_frame = None;
+        self.hide_cursor()?;
+        if self.global {
+            self.track();
+        }
+        Ok(())
+    }
+
+    /// Restore the terminal to its initial state by undoing exactly what was
+    /// enabled. This also happens when the guard is dropped, calling it more
+    /// than once does nothing.
+    pub fn restore(&mut self) -> Result<(), TermError> {
+        // someone else, like the panic hook, restored the terminal already
+        if self.global && active().take().is_none() {
+            return Ok(());
+        }
+        let enabled = std::mem::replace(&mut self.enabled, InitOptions::none());
+        enabled
+            .undo(self.terminal.backend_mut())
+            .map_err(|e| TermError::Restore(e.to_string()))
+    }
+}
+
+impl<B: TermBackend> Deref for TerminalGuard<B> {
+    type Target = Terminal<B>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl<B: TermBackend> DerefMut for TerminalGuard<B> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+impl<B: TermBackend> Drop for TerminalGuard<B> {
+    fn drop(&mut self) {
+        if let Err(e) = self.restore() {
+            eprintln!("{e}");
+        }
+    }
+}
+
+/// Run the setup steps described by `options`, recording each successful
+/// one in `enabled`. Returns the area of the inline viewport, if any.
+fn setup<B: TermBackend>(
+    backend: &mut B,
+    options: &InitOptions,
+    enabled: &mut InitOptions,
+) -> io::Result<Option<Rect>> {
+    // fail before changing anything if this is not a terminal
+    backend.size()?;
+    if options.alternate_screen && options.inline.is_none() {
+        backend.set_alternate_screen(true)?;
+        enabled.alternate_screen = true;
+    }
+    if options.raw_mode {
+        backend.set_raw_mode(true)?;
+        enabled.raw_mode = true;
+    }
+    if options.mouse_capture {
+        backend.set_mouse_capture(true)?;
+        enabled.mouse_capture = true;
+    }
+    if options.bracketed_paste {
+        backend.set_bracketed_paste(true)?;
+        enabled.bracketed_paste = true;
+    }
+    if options.focus_change {
+        backend.set_focus_change(true)?;
+        enabled.focus_change = true;
+    }
+    if let Some(flags) = options.keyboard_enhancement {
+        backend.push_keyboard_enhancement(flags)?;
+        enabled.keyboard_enhancement = Some(flags);
+    }
+    match options.inline {
+        Some(height) => {
+            let area = reserve_inline(backend, height)?;
+            enabled.inline = Some(height);
+            enabled.inline_exit = options.inline_exit;
+            enabled.inline_area = Some(area);
+            Ok(Some(area))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Make room for `height` lines starting at the cursor, scrolling the
+/// screen up if there are not enough lines below it.
+fn reserve_inline<B: TermBackend>(backend: &mut B, height: u16) -> io::Result<Rect> {
+    let size = backend.size()?;
+    let height = height.clamp(1, size.height.max(1));
+    backend.write_sequence(&"\n".repeat(height as usize - 1))?;
+    let (_, row) = backend.cursor_position()?;
+    let area = inline_area(row, height, size.width);
+    // get rid of anything that was below the cursor before
+    backend.set_cursor(0, area.y)?;
+    backend.clear_below_cursor()?;
+    Ok(area)
+}
+
+/// Area of an inline viewport whose last line is the cursor row after
+/// `height - 1` line feeds were written.
+fn inline_area(cursor_row: u16, height: u16, width: u16) -> Rect {
+    Rect {
+        x: 0,
+        y: cursor_row.saturating_sub(height.saturating_sub(1)),
+        width,
+        height,
+    }
+}
+
+/// Prepare the terminal by clearing the screen and hiding the cursor. The
+/// terminal is restored when the returned guard is dropped, and a panic hook
+/// is installed which restores it before the panic message is printed.
+/// Use `InitOptions` to enable more than that.
+pub fn init() -> Result<TerminalGuard, TermError> {
+    InitOptions::default().init()
+}
+
+/// Install a panic hook which restores the terminal before handing over to
+/// the previous hook, so the panic message and backtrace are readable.
+/// This is done by `init`, installing it more than once has no effect.
+pub fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        restore_on_panic(|| {
+            let _ = restore();
+        })
+    });
+}
+
+fn restore_on_panic<F>(restore: F)
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore();
+        previous(info);
+    }));
+}
+
+/// Restore the terminal like `restore` and print `e` to stderr
+pub fn restore_with_err<E: Display>(e: E) -> Result<(), TermError> {
     restore()?;
     eprintln!("Application error: {e}");
     Ok(())
 }
 
-/// Restore the terminal to its initial state
+/// Restore the terminal set up by `init` to its initial state. Does nothing
+/// if no terminal is set up, or it was restored already. Prefer dropping or
+/// calling `restore` on the `TerminalGuard` returned by `init`.
 pub fn restore() -> Result<(), TermError> {
-    disable_raw_mode().map_err(|e| TermError::Restore(e.to_string()))?;
-    crossterm::execute!(io::stdout(), LeaveAlternateScreen)
-        .map_err(|e| TermError::Restore(e.to_string()))?;
-
-    Ok(())
+    match active().take() {
+        Some((enabled, mut restorer)) => {
+            restorer(&enabled).map_err(|e| TermError::Restore(e.to_string()))
+        }
+        None => Ok(()),
+    }
 }
 
-/// Poll for crossterm events such as key events. This blocks the current
-/// thread for 1 second before timeout, letting the application
-/// continue execution.
+/// Poll the default backend for events such as key events. This blocks the
+/// current thread for 1 second before timeout, letting the application
+/// continue execution. Use `event::EventSource` for a configurable
+/// tick rate.
 pub fn poll_event() -> Result<Option<Event>, TermError> {
-    let outcome = match event::poll(EVENT_TIMEOUT) {
-        Ok(b) => b,
-        Err(e) => Err(TermError::Poll(e.to_string()))?,
+    DefaultInput::default().poll_event(EVENT_TIMEOUT)
+}
+
+#[cfg(all(test, feature = "crossterm"))]
+mod tests {
+    use std::{
+        io::{self, Write},
+        sync::{Arc, Mutex},
     };
-    if outcome {
-        Ok(Some(
-            event::read().map_err(|e| TermError::Poll(e.to_string()))?,
-        ))
-    } else {
-        Ok(None)
+
+    use ratatui::{backend::CrosstermBackend, layout::Rect, widgets::Paragraph};
+
+    use super::{
+        inline_area, CountingWriter, InitOptions, InlineExit, KeyboardEnhancementFlags,
+        TerminalGuard,
+    };
+
+    const ENTER_ALT: &str = "\x1b[?1049h";
+    const LEAVE_ALT: &str = "\x1b[?1049l";
+    const SHOW_CURSOR: &str = "\x1b[?25h";
+    const ENABLE_PASTE: &str = "\x1b[?2004h";
+    const DISABLE_PASTE: &str = "\x1b[?2004l";
+    const ENABLE_FOCUS: &str = "\x1b[?1004h";
+    const DISABLE_FOCUS: &str = "\x1b[?1004l";
+    const DISABLE_MOUSE: &str = "\x1b[?1000l";
+    const POP_KEYBOARD: &str = "\x1b[<1u";
+
+    /// Writer which keeps everything written to it around for inspection
+    #[derive(Clone, Default)]
+    struct FakeWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl FakeWriter {
+        fn output(&self) -> String {
+            String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+        }
+
+        fn clear(&self) {
+            self.0.lock().unwrap().clear();
+        }
+    }
+
+    impl Write for FakeWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn backend(writer: &FakeWriter) -> CrosstermBackend<FakeWriter> {
+        CrosstermBackend::new(writer.clone())
+    }
+
+    /// Default options without raw mode, which cannot be enabled in tests
+    fn options() -> InitOptions {
+        InitOptions {
+            raw_mode: false,
+            ..InitOptions::default()
+        }
+    }
+
+    #[test]
+    fn guard_restores_on_drop() {
+        let writer = FakeWriter::default();
+        let guard = TerminalGuard::new(backend(&writer), options()).unwrap();
+        assert!(writer.output().contains(ENTER_ALT));
+        assert!(!writer.output().contains(LEAVE_ALT));
+
+        drop(guard);
+        let output = writer.output();
+        assert!(output.contains(&format!("{LEAVE_ALT}{SHOW_CURSOR}")));
+    }
+
+    #[test]
+    fn restore_only_happens_once() {
+        let writer = FakeWriter::default();
+        let mut guard = TerminalGuard::new(backend(&writer), options()).unwrap();
+        guard.restore().unwrap();
+        guard.restore().unwrap();
+        drop(guard);
+        assert_eq!(writer.output().matches(LEAVE_ALT).count(), 1);
+    }
+
+    #[test]
+    fn guard_restores_on_early_return() {
+        fn fails(writer: FakeWriter) -> Result<(), io::Error> {
+            let _guard = TerminalGuard::new(backend(&writer), options()).unwrap();
+            Err(io::Error::other("oops"))?;
+            unreachable!()
+        }
+
+        let writer = FakeWriter::default();
+        assert!(fails(writer.clone()).is_err());
+        assert!(writer.output().contains(LEAVE_ALT));
+    }
+
+    #[test]
+    fn restore_undoes_what_was_enabled() {
+        let writer = FakeWriter::default();
+        let options = options()
+            .bracketed_paste(true)
+            .focus_change(true)
+            .keyboard_enhancement(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES);
+        let mut guard = TerminalGuard::new(backend(&writer), options).unwrap();
+        assert!(writer.output().contains(ENABLE_PASTE));
+        assert!(writer.output().contains(ENABLE_FOCUS));
+
+        writer.clear();
+        guard.restore().unwrap();
+        let output = writer.output();
+        assert!(output.contains(DISABLE_PASTE));
+        assert!(output.contains(DISABLE_FOCUS));
+        assert!(output.contains(POP_KEYBOARD));
+        assert!(!output.contains(DISABLE_MOUSE));
+    }
+
+    #[test]
+    fn skipped_alternate_screen_and_visible_cursor_are_left_alone() {
+        let writer = FakeWriter::default();
+        let options = options().alternate_screen(false).show_cursor(true);
+        let mut guard = TerminalGuard::new(backend(&writer), options).unwrap();
+        assert!(!writer.output().contains(ENTER_ALT));
+
+        guard.restore().unwrap();
+        assert!(!writer.output().contains(LEAVE_ALT));
+        assert!(!writer.output().contains(SHOW_CURSOR));
+    }
+
+    #[test]
+    fn resume_sets_up_again_after_restore() {
+        let writer = FakeWriter::default();
+        let options = options().bracketed_paste(true);
+        let mut guard = TerminalGuard::new(backend(&writer), options).unwrap();
+        guard.restore().unwrap();
+
+        writer.clear();
+        guard.resume().unwrap();
+        assert!(writer.output().contains(ENTER_ALT));
+        assert!(writer.output().contains(ENABLE_PASTE));
+
+        writer.clear();
+        drop(guard);
+        assert!(writer.output().contains(LEAVE_ALT));
+        assert!(writer.output().contains(DISABLE_PASTE));
+    }
+
+    #[test]
+    fn draws_are_synchronized() {
+        let writer = FakeWriter::default();
+        let options = options().synchronized_output(true);
+        let mut guard = TerminalGuard::new(backend(&writer), options).unwrap();
+
+        writer.clear();
+        guard
+            .draw(|f| f.render_widget(Paragraph::new("hi"), f.size()))
+            .unwrap();
+        let output = writer.output();
+        assert!(output.starts_with("\x1b[?2026h"));
+        assert!(output.ends_with("\x1b[?2026l"));
+    }
+
+    #[test]
+    fn failed_draws_end_the_synchronized_update() {
+        /// Fails when asked to write a `!`
+        struct Rejecting(FakeWriter);
+
+        impl Write for Rejecting {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                match buf.contains(&b'!') {
+                    true => Err(io::Error::other("rejected")),
+                    false => self.0.write(buf),
+                }
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let writer = FakeWriter::default();
+        let options = options().synchronized_output(true);
+        let backend = CrosstermBackend::new(Rejecting(writer.clone()));
+        let mut guard = TerminalGuard::new(backend, options).unwrap();
+
+        writer.clear();
+        let drawn = guard.draw(|f| f.render_widget(Paragraph::new("!"), f.size()));
+        assert!(drawn.is_err());
+        assert!(writer.output().ends_with("\x1b[?2026l"));
+    }
+
+    #[test]
+    fn frame_stats_count_changes() {
+        let writer = CountingWriter::new(FakeWriter::default());
+        let counter = writer.counter();
+        let options = options().synchronized_output(false);
+        let mut guard = TerminalGuard::new(CrosstermBackend::new(writer), options)
+            .unwrap()
+            .count_bytes(counter);
+
+        let mut draw = |text: &'static str| {
+            guard
+                .draw(|f| f.render_widget(Paragraph::new(text), f.size()))
+                .unwrap()
+        };
+        let first = draw("hi");
+        assert_eq!(first.cells_changed, 2);
+        assert!(first.bytes_written.unwrap() > 0);
+        assert_eq!(draw("hi").cells_changed, 0);
+        assert_eq!(draw("ho").cells_changed, 1);
+        assert_eq!(guard.last_frame_stats().unwrap().cells_changed, 1);
+    }
+
+    #[test]
+    fn inline_area_below_cursor() {
+        assert_eq!(inline_area(7, 3, 80), Rect::new(0, 5, 80, 3));
+        // a cursor near the top never produces a negative offset
+        assert_eq!(inline_area(0, 3, 80), Rect::new(0, 0, 80, 3));
+    }
+
+    #[test]
+    fn inline_exit_keep_moves_below_viewport() {
+        let writer = FakeWriter::default();
+        let enabled = InitOptions {
+            inline_area: Some(Rect::new(0, 5, 80, 3)),
+            ..InitOptions::none()
+        };
+        enabled.undo(&mut backend(&writer)).unwrap();
+        // rows are 1-based in the escape sequence
+        assert_eq!(writer.output(), "\x1b[8;1H\r\n");
+    }
+
+    #[test]
+    fn inline_exit_clear_clears_viewport() {
+        let writer = FakeWriter::default();
+        let enabled = InitOptions {
+            inline_area: Some(Rect::new(0, 5, 80, 3)),
+            inline_exit: InlineExit::Clear,
+            ..InitOptions::none()
+        };
+        enabled.undo(&mut backend(&writer)).unwrap();
+        assert_eq!(writer.output(), "\x1b[6;1H\x1b[J");
     }
 }
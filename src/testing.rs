@@ -0,0 +1,412 @@
+use std::{io, time::Duration};
+
+use ratatui::{
+    backend::{Backend, TestBackend},
+    buffer::{Buffer, Cell},
+    layout::Rect,
+    style::Style,
+    Terminal,
+};
+
+use crate::{
+    component::{Component, ComponentError},
+    event::{MouseButton, MouseEvent, MouseEventKind},
+    keys::{KeyCode, KeyEvent, KeyModifiers},
+    snapshot::Snapshot,
+};
+
+/// Wraps `TestBackend` to keep track of the cursor, which `TestBackend`
+/// does not expose.
+struct HarnessBackend {
+    inner: TestBackend,
+    cursor_visible: bool,
+    cursor: (u16, u16),
+}
+
+impl Backend for HarnessBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        self.inner.draw(content)
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.cursor_visible = false;
+        self.inner.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.cursor_visible = true;
+        self.inner.show_cursor()
+    }
+
+    fn get_cursor(&mut self) -> io::Result<(u16, u16)> {
+        Ok(self.cursor)
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
+        self.cursor = (x, y);
+        self.inner.set_cursor(x, y)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.inner.clear()
+    }
+
+    fn size(&self) -> io::Result<Rect> {
+        self.inner.size()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Mounts a `Component` on a headless `TestBackend` so it can be driven by
+/// scripted events and its rendered output asserted on. The component is
+/// redrawn after every event.
+/// ```
+/// use ratatui::{backend::Backend, widgets::Paragraph, Frame};
+/// use tui_utils::{component::Component, keys::KeyCode, testing::{self, Harness}};
+///
+/// struct Hello;
+///
+/// impl Component for Hello {
+///     type Message = ();
+///     fn draw<B: Backend>(&mut self, f: &mut Frame<B>, _dim: bool) {
+///         f.render_widget(Paragraph::new("hello"), f.size());
+///     }
+/// }
+///
+/// let mut harness = Harness::new(Hello, 5, 1);
+/// harness.key(testing::key(KeyCode::Enter)).unwrap();
+/// harness.assert_lines(&["hello"]);
+/// ```
+pub struct Harness<C: Component> {
+    component: C,
+    terminal: Terminal<HarnessBackend>,
+    dim: bool,
+}
+
+impl<C: Component> Harness<C> {
+    /// Mount `component` on a terminal of the given size and draw it once.
+    pub fn new(component: C, width: u16, height: u16) -> Self {
+        let backend = HarnessBackend {
+            inner: TestBackend::new(width, height),
+            cursor_visible: false,
+            cursor: (0, 0),
+        };
+        let terminal = Terminal::new(backend).expect("the test backend never fails");
+        let mut harness = Self {
+            component,
+            terminal,
+            dim: false,
+        };
+        harness.component.mount();
+        harness.draw();
+        harness
+    }
+
+    /// Draw the component with dimming enabled or disabled from now on
+    pub fn set_dim(&mut self, dim: bool) {
+        self.dim = dim;
+        self.draw();
+    }
+
+    /// Redraw the component
+    pub fn draw(&mut self) {
+        let dim = self.dim;
+        let component = &mut self.component;
+        self.terminal
+            .draw(|f| component.draw(f, dim))
+            .expect("the test backend never fails");
+    }
+
+    /// Feed a key event to the component and return the message it produced
+    pub fn key(&mut self, key: KeyEvent) -> Result<C::Message, ComponentError> {
+        let message = self.component.handle_input(key);
+        self.draw();
+        message
+    }
+
+    /// Feed a sequence of key events, stopping at the first error
+    pub fn keys<I>(&mut self, keys: I) -> Result<Vec<C::Message>, ComponentError>
+    where
+        I: IntoIterator<Item = KeyEvent>,
+    {
+        keys.into_iter().map(|k| self.key(k)).collect()
+    }
+
+    /// Feed every character of `text` as a key press
+    pub fn type_str(&mut self, text: &str) -> Result<Vec<C::Message>, ComponentError> {
+        self.keys(text.chars().map(|c| key(KeyCode::Char(c))))
+    }
+
+    /// Feed a mouse event to the component and return the message it produced
+    pub fn mouse(&mut self, mouse: MouseEvent) -> Result<C::Message, ComponentError> {
+        let message = self.component.handle_mouse(mouse);
+        self.draw();
+        message
+    }
+
+    /// Shorthand for a left click at the given cell
+    pub fn click(&mut self, column: u16, row: u16) -> Result<C::Message, ComponentError> {
+        self.mouse(mouse(MouseEventKind::Down(MouseButton::Left), column, row))
+    }
+
+    /// Resize the terminal and notify the component
+    pub fn resize(&mut self, width: u16, height: u16) -> C::Message {
+        self.terminal.backend_mut().inner.resize(width, height);
+        let message = self.component.resize(width, height);
+        self.draw();
+        message
+    }
+
+    /// Tick the component as if `elapsed` time had passed
+    pub fn tick(&mut self, elapsed: Duration) -> C::Message {
+        let message = self.component.tick(elapsed);
+        self.draw();
+        message
+    }
+
+    pub fn component(&self) -> &C {
+        &self.component
+    }
+
+    pub fn component_mut(&mut self) -> &mut C {
+        &mut self.component
+    }
+
+    /// Unmount the component and hand it back
+    pub fn into_inner(mut self) -> C {
+        self.component.unmount();
+        self.component
+    }
+
+    /// The buffer as it was last drawn
+    pub fn buffer(&self) -> &Buffer {
+        self.terminal.backend().inner.buffer()
+    }
+
+    /// The rendered symbols of each row, without any styling
+    pub fn lines(&self) -> Vec<String> {
+        let buffer = self.buffer();
+        let area = buffer.area();
+        (area.top()..area.bottom())
+            .map(|y| {
+                (area.left()..area.right())
+                    .map(|x| buffer.get(x, y).symbol.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The cell at the given position
+    pub fn cell(&self, x: u16, y: u16) -> &Cell {
+        self.buffer().get(x, y)
+    }
+
+    /// The cursor position if the component made the cursor visible in its last draw
+    pub fn cursor(&self) -> Option<(u16, u16)> {
+        let backend = self.terminal.backend();
+        backend.cursor_visible.then_some(backend.cursor)
+    }
+
+    /// Assert that the rendered rows match `expected`, ignoring styles
+    pub fn assert_lines(&self, expected: &[&str]) {
+        let actual = self.lines();
+        assert_eq!(
+            actual,
+            expected,
+            "rendered buffer does not match\n{}",
+            diff_lines(expected, &actual)
+        );
+    }
+
+    /// Assert that the cell at the given position has every property set in
+    /// `style`. Properties left unset in `style` are not checked.
+    pub fn assert_style(&self, x: u16, y: u16, style: Style) {
+        let cell = self.cell(x, y);
+        let matches = style.fg.is_none_or(|fg| fg == cell.fg)
+            && style.bg.is_none_or(|bg| bg == cell.bg)
+            && cell.modifier.contains(style.add_modifier)
+            && !cell.modifier.intersects(style.sub_modifier);
+        assert!(
+            matches,
+            "unexpected style of cell ({x}, {y})\nexpected: {style:?}\n  actual: {:?}",
+            cell.style()
+        );
+    }
+
+    /// Compare the rendered buffer against the snapshot called `name`
+    /// stored in the default snapshot directory.
+    pub fn assert_snapshot(&self, name: &str) {
+        Snapshot::default().assert(name, self.buffer());
+    }
+
+    /// Assert that the cursor is visible at the given position
+    pub fn assert_cursor(&self, x: u16, y: u16) {
+        assert_eq!(self.cursor(), Some((x, y)), "unexpected cursor position");
+    }
+}
+
+fn diff_lines(expected: &[&str], actual: &[String]) -> String {
+    let rows = expected.len().max(actual.len());
+    (0..rows)
+        .map(|i| {
+            let e = expected.get(i).copied().unwrap_or_default();
+            let a = actual.get(i).map(String::as_str).unwrap_or_default();
+            let marker = if e == a { ' ' } else { '!' };
+            format!("{marker} expected: {e:?}\n{marker}   actual: {a:?}\n")
+        })
+        .collect()
+}
+
+/// Construct a key press without modifiers
+pub fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::empty())
+}
+
+/// Construct a key press with modifiers
+pub fn key_with(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+    KeyEvent::new(code, modifiers)
+}
+
+/// Construct a mouse event at the given cell without modifiers
+pub fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+    MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers: KeyModifiers::empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ratatui::{
+        backend::Backend,
+        style::{Color, Style},
+        text::Span,
+        widgets::Paragraph,
+        Frame,
+    };
+
+    use super::{key, mouse, Harness};
+    use crate::{
+        component::{Component, ComponentError},
+        event::{MouseEvent, MouseEventKind},
+        keys::{KeyCode, KeyEvent},
+    };
+
+    #[derive(Default, Debug, PartialEq)]
+    enum Message {
+        #[default]
+        Idle,
+        Submit(String),
+        Clicked(u16, u16),
+    }
+
+    #[derive(Default)]
+    struct Input {
+        text: String,
+        mounted: bool,
+        ticks: u32,
+    }
+
+    impl Component for Input {
+        type Message = Message;
+        fn draw<B: Backend>(&mut self, f: &mut Frame<B>, _dim: bool) {
+            let span = Span::styled(self.text.as_str(), Style::default().fg(Color::Red));
+            f.render_widget(Paragraph::new(span), f.size());
+            f.set_cursor(self.text.len() as u16, 0);
+        }
+
+        fn handle_input(&mut self, key: KeyEvent) -> Result<Self::Message, ComponentError> {
+            match key.code {
+                KeyCode::Char(c) => self.text.push(c),
+                KeyCode::Enter => return Ok(Message::Submit(self.text.clone())),
+                _ => return Err(ComponentError::recoverable("unsupported key")),
+            }
+            Ok(Message::Idle)
+        }
+
+        fn handle_mouse(&mut self, mouse: MouseEvent) -> Result<Self::Message, ComponentError> {
+            Ok(Message::Clicked(mouse.column, mouse.row))
+        }
+
+        fn mount(&mut self) {
+            self.mounted = true;
+        }
+
+        fn unmount(&mut self) {
+            self.mounted = false;
+        }
+
+        fn tick(&mut self, _elapsed: Duration) -> Self::Message {
+            self.ticks += 1;
+            Message::Idle
+        }
+    }
+
+    #[test]
+    fn mounts_and_draws_on_creation() {
+        let harness = Harness::new(Input::default(), 4, 1);
+        assert!(harness.component().mounted);
+        harness.assert_lines(&["    "]);
+    }
+
+    #[test]
+    fn keys_are_rendered_and_messages_returned() {
+        let mut harness = Harness::new(Input::default(), 4, 2);
+        harness.type_str("ab").unwrap();
+        harness.assert_lines(&["ab  ", "    "]);
+
+        let message = harness.key(key(KeyCode::Enter)).unwrap();
+        assert_eq!(message, Message::Submit(String::from("ab")));
+    }
+
+    #[test]
+    fn errors_are_returned() {
+        let mut harness = Harness::new(Input::default(), 4, 1);
+        assert!(harness.key(key(KeyCode::Tab)).is_err());
+    }
+
+    #[test]
+    fn mouse_events_are_forwarded() {
+        let mut harness = Harness::new(Input::default(), 4, 1);
+        let message = harness.mouse(mouse(MouseEventKind::Moved, 2, 0)).unwrap();
+        assert_eq!(message, Message::Clicked(2, 0));
+        assert_eq!(harness.click(1, 0).unwrap(), Message::Clicked(1, 0));
+    }
+
+    #[test]
+    fn styles_and_cursor_are_tracked() {
+        let mut harness = Harness::new(Input::default(), 4, 1);
+        harness.type_str("x").unwrap();
+        harness.assert_style(0, 0, Style::default().fg(Color::Red));
+        harness.assert_cursor(1, 0);
+    }
+
+    #[test]
+    fn resize_redraws_with_new_size() {
+        let mut harness = Harness::new(Input::default(), 4, 1);
+        harness.type_str("abc").unwrap();
+        harness.resize(2, 1);
+        harness.assert_lines(&["ab"]);
+    }
+
+    #[test]
+    fn tick_and_unmount() {
+        let mut harness = Harness::new(Input::default(), 4, 1);
+        harness.tick(Duration::from_millis(10));
+        harness.tick(Duration::from_millis(10));
+        assert_eq!(harness.component().ticks, 2);
+
+        let input = harness.into_inner();
+        assert!(!input.mounted);
+    }
+}
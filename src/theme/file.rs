@@ -0,0 +1,600 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
+};
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::{
+    de::{self, value::MapAccessDeserializer, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use thiserror::Error;
+
+use super::Theme;
+
+#[derive(Error, Debug)]
+pub enum ThemeError {
+    #[error("failed to read theme {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// The theme could not be parsed or has invalid values, with the line
+    /// and column of the problem, both starting at 1
+    #[error("{}line {line}, column {column}: {message}", file_prefix(path))]
+    Invalid {
+        path: Option<PathBuf>,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("unsupported theme file {}, expected .toml or .json", path.display())]
+    UnknownFormat { path: PathBuf },
+    #[error("theme {} inherits from itself", path.display())]
+    Cycle { path: PathBuf },
+}
+
+fn file_prefix(path: &Option<PathBuf>) -> String {
+    path.as_ref()
+        .map(|p| format!("{}: ", p.display()))
+        .unwrap_or_default()
+}
+
+/// A theme as written in a file. Every role is optional and taken from the
+/// base theme when left out.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    /// `"dark"`, `"light"` or the path of another theme file, relative to
+    /// this one
+    base: Option<String>,
+    border: Option<StyleSpec>,
+    border_focused: Option<StyleSpec>,
+    highlight: Option<StyleSpec>,
+    dimmed: Option<StyleSpec>,
+    error: Option<StyleSpec>,
+    warning: Option<StyleSpec>,
+    title: Option<StyleSpec>,
+    accent: Option<StyleSpec>,
+}
+
+impl ThemeFile {
+    fn apply(self, base: Theme) -> Theme {
+        let or = |spec: Option<StyleSpec>, style| spec.map_or(style, |s| s.0);
+        Theme {
+            border: or(self.border, base.border),
+            border_focused: or(self.border_focused, base.border_focused),
+            highlight: or(self.highlight, base.highlight),
+            dimmed: or(self.dimmed, base.dimmed),
+            error: or(self.error, base.error),
+            warning: or(self.warning, base.warning),
+            title: or(self.title, base.title),
+            accent: or(self.accent, base.accent),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Toml,
+    Json,
+}
+
+impl Format {
+    fn of(path: &Path) -> Result<Self, ThemeError> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Ok(Self::Toml),
+            Some("json") => Ok(Self::Json),
+            _ => Err(ThemeError::UnknownFormat { path: path.into() }),
+        }
+    }
+
+    fn parse(self, source: &str, path: Option<&Path>) -> Result<ThemeFile, ThemeError> {
+        let invalid = |(line, column), message| ThemeError::Invalid {
+            path: path.map(Into::into),
+            line,
+            column,
+            message,
+        };
+        match self {
+            Self::Toml => toml::from_str(source).map_err(|e| {
+                let start = e.span().map_or(0, |span| span.start);
+                invalid(line_column(source, start), e.message().to_string())
+            }),
+            Self::Json => serde_json::from_str(source).map_err(|e| {
+                let location = format!(" at line {} column {}", e.line(), e.column());
+                let message = e.to_string().trim_end_matches(&location).to_string();
+                invalid((e.line(), e.column()), message)
+            }),
+        }
+    }
+}
+
+/// Line and column of byte `offset` in `source`, both starting at 1
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+impl Theme {
+    /// Parse a theme written in TOML. Roles which are left out come from
+    /// the `base` theme, the dark one by default.
+    /// ```
+    /// use ratatui::style::{Color, Modifier};
+    /// use tui_utils::theme::Theme;
+    ///
+    /// let theme = Theme::from_toml(
+    ///     r##"
+    ///     base = "light"
+    ///     border = "#5f87af"
+    ///     highlight = { bg = 238, modifiers = ["bold", "italic"] }
+    ///     error = { fg = "light_red" }
+    ///     "##,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(theme.border.fg, Some(Color::Rgb(0x5f, 0x87, 0xaf)));
+    /// assert!(theme.highlight.add_modifier.contains(Modifier::ITALIC));
+    /// assert_eq!(theme.accent, Theme::light().accent);
+    /// ```
+    pub fn from_toml(source: &str) -> Result<Self, ThemeError> {
+        resolve(Format::Toml, source, None, &mut Vec::new())
+    }
+
+    /// Parse a theme written in JSON, with the same keys as `from_toml`
+    pub fn from_json(source: &str) -> Result<Self, ThemeError> {
+        resolve(Format::Json, source, None, &mut Vec::new())
+    }
+
+    /// Load a theme from a `.toml` or `.json` file. A `base` which is not
+    /// a built-in theme is loaded from a path relative to the file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ThemeError> {
+        load(path.as_ref(), &mut Vec::new())
+    }
+}
+
+/// Load the theme at `path`, adding it and the files it inherits from to
+/// `files`
+fn load(path: &Path, files: &mut Vec<PathBuf>) -> Result<Theme, ThemeError> {
+    let io_error = |source| ThemeError::Io {
+        path: path.into(),
+        source,
+    };
+    let canonical = path.canonicalize().map_err(io_error)?;
+    if files.contains(&canonical) {
+        return Err(ThemeError::Cycle { path: path.into() });
+    }
+    files.push(canonical);
+    let source = fs::read_to_string(path).map_err(io_error)?;
+    resolve(Format::of(path)?, &source, Some(path), files)
+}
+
+fn resolve(
+    format: Format,
+    source: &str,
+    path: Option<&Path>,
+    files: &mut Vec<PathBuf>,
+) -> Result<Theme, ThemeError> {
+    let file = format.parse(source, path)?;
+    let base = match file.base.as_deref() {
+        None | Some("dark") => Theme::dark(),
+        Some("light") => Theme::light(),
+        Some(base) => {
+            let dir = path.and_then(Path::parent).unwrap_or(Path::new(""));
+            load(&dir.join(base), files)?
+        }
+    };
+    Ok(file.apply(base))
+}
+
+/// A style in a theme file, either just the foreground color or a table
+/// with `fg`, `bg` and `modifiers`
+struct StyleSpec(Style);
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StyleTable {
+    fg: Option<ThemeColor>,
+    bg: Option<ThemeColor>,
+    #[serde(default)]
+    modifiers: Vec<ModifierName>,
+}
+
+impl<'de> Deserialize<'de> for StyleSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SpecVisitor;
+
+        impl<'de> Visitor<'de> for SpecVisitor {
+            type Value = StyleSpec;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a color or a table with fg, bg and modifiers")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<StyleSpec, A::Error> {
+                let table = StyleTable::deserialize(MapAccessDeserializer::new(map))?;
+                let mut style = Style {
+                    fg: table.fg.map(|c| c.0),
+                    bg: table.bg.map(|c| c.0),
+                    ..Style::default()
+                };
+                for modifier in table.modifiers {
+                    style = style.add_modifier(modifier.into());
+                }
+                Ok(StyleSpec(style))
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<StyleSpec, E> {
+                ColorVisitor
+                    .visit_str(s)
+                    .map(|c| StyleSpec(Style::default().fg(c.0)))
+            }
+
+            fn visit_u64<E: de::Error>(self, n: u64) -> Result<StyleSpec, E> {
+                ColorVisitor
+                    .visit_u64(n)
+                    .map(|c| StyleSpec(Style::default().fg(c.0)))
+            }
+
+            fn visit_i64<E: de::Error>(self, n: i64) -> Result<StyleSpec, E> {
+                ColorVisitor
+                    .visit_i64(n)
+                    .map(|c| StyleSpec(Style::default().fg(c.0)))
+            }
+        }
+
+        deserializer.deserialize_any(SpecVisitor)
+    }
+}
+
+/// A `#rrggbb` hex color, a color name like `light_blue` or an ANSI index
+struct ThemeColor(Color);
+
+struct ColorVisitor;
+
+impl<'de> Visitor<'de> for ColorVisitor {
+    type Value = ThemeColor;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a #rrggbb hex color, a color name or an ANSI index from 0 to 255")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<ThemeColor, E> {
+        parse_color(s)
+            .map(ThemeColor)
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Str(s), &self))
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<ThemeColor, E> {
+        u8::try_from(n)
+            .map(|i| ThemeColor(Color::Indexed(i)))
+            .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(n), &self))
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<ThemeColor, E> {
+        u8::try_from(n)
+            .map(|i| ThemeColor(Color::Indexed(i)))
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(n), &self))
+    }
+}
+
+impl<'de> Deserialize<'de> for ThemeColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ColorVisitor)
+    }
+}
+
+fn parse_color(s: &str) -> Option<Color> {
+    if let Some(hex) = s.strip_prefix('#') {
+        // `from_str_radix` would accept signs like in `#+f+f+f`
+        if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+    // `light_blue`, `light-blue` and `LightBlue` all work
+    let name: String = s
+        .chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' '))
+        .collect::<String>()
+        .to_lowercase();
+    Some(match name.as_str() {
+        "reset" | "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ModifierName {
+    Bold,
+    Dim,
+    Italic,
+    Underlined,
+    SlowBlink,
+    RapidBlink,
+    Reversed,
+    Hidden,
+    CrossedOut,
+}
+
+impl From<ModifierName> for Modifier {
+    fn from(name: ModifierName) -> Self {
+        match name {
+            ModifierName::Bold => Modifier::BOLD,
+            ModifierName::Dim => Modifier::DIM,
+            ModifierName::Italic => Modifier::ITALIC,
+            ModifierName::Underlined => Modifier::UNDERLINED,
+            ModifierName::SlowBlink => Modifier::SLOW_BLINK,
+            ModifierName::RapidBlink => Modifier::RAPID_BLINK,
+            ModifierName::Reversed => Modifier::REVERSED,
+            ModifierName::Hidden => Modifier::HIDDEN,
+            ModifierName::CrossedOut => Modifier::CROSSED_OUT,
+        }
+    }
+}
+
+/// How often a `ThemeWatcher` checks its files by default
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Reloads a theme file on a background thread whenever it or a file it
+/// inherits from changes, and makes it the active theme. Files are checked
+/// by their modification time. The thread is stopped when the watcher is
+/// dropped.
+/// ```no_run
+/// use tui_utils::{event::EventReader, theme::ThemeWatcher};
+///
+/// let reader = EventReader::<()>::new(std::time::Duration::from_millis(250));
+/// let sender = reader.sender();
+/// let _watcher = ThemeWatcher::new("theme.toml", move |result| {
+///     // redraw with the new theme, or show why it was not loaded
+///     let _ = sender.send(());
+/// });
+/// ```
+pub struct ThemeWatcher {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ThemeWatcher {
+    /// Watch the theme at `path`. `on_reload` is called after each reload
+    /// with the new theme, or with the error if the file is invalid, in
+    /// which case the active theme is left alone. The theme is not loaded
+    /// until the file first changes, so load it with `Theme::load` first;
+    /// changes made once this returns are picked up.
+    pub fn new<F>(path: impl Into<PathBuf>, on_reload: F) -> Self
+    where
+        F: FnMut(Result<Theme, ThemeError>) + Send + 'static,
+    {
+        Self::with_interval(path, WATCH_INTERVAL, on_reload)
+    }
+
+    /// Like `new`, checking the files every `interval`
+    pub fn with_interval<F>(path: impl Into<PathBuf>, interval: Duration, on_reload: F) -> Self
+    where
+        F: FnMut(Result<Theme, ThemeError>) + Send + 'static,
+    {
+        let path = path.into();
+        // look at the files before returning, so no change made after is missed
+        let mut files = Vec::new();
+        let _ = load_watched(&path, &mut files);
+        let last = modified(&files);
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = stop.clone();
+            thread::spawn(move || watch(path, files, last, interval, on_reload, stop))
+        };
+        Self {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for ThemeWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+fn modified(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files
+        .iter()
+        .map(|f| fs::metadata(f).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// Load the theme at `path`, collecting it and the files it inherits from
+/// in `files`, or just the theme while it cannot be read
+fn load_watched(path: &Path, files: &mut Vec<PathBuf>) -> Result<Theme, ThemeError> {
+    files.clear();
+    let result = load(path, files);
+    if files.is_empty() {
+        files.push(path.into());
+    }
+    result
+}
+
+/// Body of the watcher thread, `last` holds the modification times of `files`
+fn watch<F>(
+    path: PathBuf,
+    mut files: Vec<PathBuf>,
+    mut last: Vec<Option<SystemTime>>,
+    interval: Duration,
+    mut on_reload: F,
+    stop: Arc<AtomicBool>,
+) where
+    F: FnMut(Result<Theme, ThemeError>),
+{
+    loop {
+        thread::park_timeout(interval);
+        if stop.load(Ordering::Relaxed) {
+            return;
+        }
+        if modified(&files) == last {
+            continue;
+        }
+        let result = load_watched(&path, &mut files);
+        last = modified(&files);
+        if let Ok(theme) = result {
+            super::set(theme);
+        }
+        on_reload(result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs,
+        path::{Path, PathBuf},
+        sync::mpsc,
+        time::{Duration, SystemTime},
+    };
+
+    use ratatui::style::{Color, Modifier, Style};
+
+    use super::{Theme, ThemeError, ThemeWatcher};
+
+    /// A directory for the files of one test, removed when dropped
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn temp_dir(name: &str) -> TempDir {
+        let dir = env::temp_dir().join(format!("tui-utils-themes-{}-{name}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    #[test]
+    fn colors_and_modifiers_parse() {
+        let theme = Theme::from_json(
+            r##"{
+                "border": "DarkGray",
+                "border_focused": { "fg": "#FF8000", "modifiers": ["bold", "underlined"] },
+                "dimmed": 244,
+                "error": { "fg": "light-red", "bg": "reset" }
+            }"##,
+        )
+        .unwrap();
+        assert_eq!(theme.border, Style::default().fg(Color::DarkGray));
+        assert_eq!(
+            theme.border_focused,
+            Style::default()
+                .fg(Color::Rgb(255, 128, 0))
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+        );
+        assert_eq!(theme.dimmed, Style::default().fg(Color::Indexed(244)));
+        assert_eq!(theme.error.bg, Some(Color::Reset));
+        assert_eq!(theme.warning, Theme::dark().warning);
+    }
+
+    #[test]
+    fn invalid_themes_report_the_location() {
+        let toml = "border = \"red\"\naccent = { fg = \"#12345\" }\n";
+        match Theme::from_toml(toml) {
+            Err(ThemeError::Invalid { line, column, .. }) => assert_eq!((line, column), (2, 17)),
+            other => panic!("unexpected {other:?}"),
+        }
+        let json = "{\n  \"title\": { \"modifiers\": [\"blod\"] }\n}";
+        let error = Theme::from_json(json).unwrap_err();
+        assert!(matches!(error, ThemeError::Invalid { line: 2, .. }));
+        assert!(error.to_string().contains("unknown variant `blod`"));
+        assert!(Theme::from_toml("backround = \"red\"").is_err());
+        assert!(Theme::from_toml("dimmed = 256").is_err());
+        assert!(Theme::from_toml("accent = \"#+f+f+f\"").is_err());
+    }
+
+    #[test]
+    fn themes_inherit_from_files() {
+        let temp = temp_dir("inherit");
+        let dir = temp.path();
+        fs::write(
+            dir.join("base.json"),
+            r#"{"base": "light", "accent": "green"}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("theme.toml"), "base = \"base.json\"\nborder = 1\n").unwrap();
+        let theme = Theme::load(dir.join("theme.toml")).unwrap();
+        assert_eq!(theme.accent, Style::default().fg(Color::Green));
+        assert_eq!(theme.border, Style::default().fg(Color::Indexed(1)));
+        assert_eq!(theme.highlight, Theme::light().highlight);
+
+        fs::write(dir.join("loop.toml"), "base = \"loop.toml\"").unwrap();
+        assert!(matches!(
+            Theme::load(dir.join("loop.toml")),
+            Err(ThemeError::Cycle { .. })
+        ));
+    }
+
+    #[test]
+    fn watcher_reloads_changed_files() {
+        let dir = temp_dir("watch");
+        let path = dir.path().join("theme.toml");
+        fs::write(&path, "base = \"dark\"").unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let _watcher = ThemeWatcher::with_interval(&path, Duration::from_millis(10), move |r| {
+            sender.send(r.map_err(|e| e.to_string())).unwrap();
+        });
+
+        // the watcher has seen the first version once it is created, so
+        // changes right after are reloaded
+        let mut stamp = 0;
+        let mut write = |contents: &str| {
+            fs::write(&path, contents).unwrap();
+            // a new modification time, even on coarse file systems
+            stamp += 1;
+            let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(stamp);
+            let file = fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(modified).unwrap();
+            receiver.recv_timeout(Duration::from_secs(5)).unwrap()
+        };
+
+        let theme = write("title = { modifiers = [\"bold\"] }");
+        assert_eq!(theme, Ok(Theme::dark()));
+        let error = write("title = \"nope\"");
+        assert!(error.unwrap_err().contains("line 1, column 9"));
+    }
+}
@@ -0,0 +1,187 @@
+use std::sync::{PoisonError, RwLock};
+
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::caps::{self, Capabilities, ColorSupport};
+
+mod file;
+
+pub use file::{ThemeError, ThemeWatcher};
+
+/// What a style of a `Theme` is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// Borders of blocks without the focus
+    Border,
+    /// Borders of the focused block
+    BorderFocused,
+    /// The selected item of lists and tables
+    Highlight,
+    /// Content behind a popup or otherwise inactive
+    Dimmed,
+    Error,
+    Warning,
+    /// Titles of blocks
+    Title,
+    /// Anything that should stand out, like a dragged divider
+    Accent,
+}
+
+/// Styles for each `Role`, so apps and the helpers in `blocks` and
+/// `style` do not hardcode colors. Colors are downgraded to what the
+/// terminal supports when they are looked up with `style`. Themes can be
+/// loaded from TOML or JSON files with `Theme::load`.
+/// ```
+/// use ratatui::style::{Color, Style};
+/// use tui_utils::theme::{self, Role, Theme};
+///
+/// theme::set(Theme {
+///     accent: Style::default().fg(Color::Magenta),
+///     ..Theme::light()
+/// });
+/// let border = theme::style(Role::Border);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub border: Style,
+    pub border_focused: Style,
+    pub highlight: Style,
+    pub dimmed: Style,
+    pub error: Style,
+    pub warning: Style,
+    pub title: Style,
+    pub accent: Style,
+}
+
+impl Theme {
+    /// For terminals with a dark background, the default
+    pub fn dark() -> Self {
+        Self {
+            border: Style::default().fg(Color::White),
+            border_focused: Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+            highlight: Style::default()
+                .bg(Color::Indexed(8))
+                .add_modifier(Modifier::BOLD),
+            dimmed: Style::default().fg(Color::Indexed(8)),
+            error: Style::default().fg(Color::Red),
+            warning: Style::default().fg(Color::Yellow),
+            title: Style::default().add_modifier(Modifier::BOLD),
+            accent: Style::default().fg(Color::Cyan),
+        }
+    }
+
+    /// For terminals with a light background
+    pub fn light() -> Self {
+        Self {
+            border: Style::default().fg(Color::Black),
+            border_focused: Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+            highlight: Style::default()
+                .bg(Color::Indexed(252))
+                .add_modifier(Modifier::BOLD),
+            dimmed: Style::default().fg(Color::Indexed(244)),
+            error: Style::default().fg(Color::Red),
+            warning: Style::default().fg(Color::Indexed(130)),
+            title: Style::default().add_modifier(Modifier::BOLD),
+            accent: Style::default().fg(Color::Blue),
+        }
+    }
+
+    /// The style for `role` as it is defined in the theme
+    pub fn get(&self, role: Role) -> Style {
+        match role {
+            Role::Border => self.border,
+            Role::BorderFocused => self.border_focused,
+            Role::Highlight => self.highlight,
+            Role::Dimmed => self.dimmed,
+            Role::Error => self.error,
+            Role::Warning => self.warning,
+            Role::Title => self.title,
+            Role::Accent => self.accent,
+        }
+    }
+
+    /// The style for `role` adapted to `caps`. Without colors the
+    /// highlight is reversed and dimmed content uses the dim modifier, so
+    /// both stay visible.
+    pub fn style_for(&self, role: Role, caps: &Capabilities) -> Style {
+        let style = caps.adapt_style(self.get(role));
+        match (caps.color, role) {
+            (ColorSupport::None, Role::Highlight) => style.add_modifier(Modifier::REVERSED),
+            (ColorSupport::None, Role::Dimmed) => style.add_modifier(Modifier::DIM),
+            _ => style,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+static CURRENT: RwLock<Option<Theme>> = RwLock::new(None);
+
+/// The active theme, the dark one unless `set` was called
+pub fn current() -> Theme {
+    CURRENT
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .unwrap_or_default()
+}
+
+/// Replace the active theme
+pub fn set(theme: Theme) {
+    *CURRENT.write().unwrap_or_else(PoisonError::into_inner) = Some(theme);
+}
+
+/// The style for `role` in the active theme, adapted to the terminal
+pub fn style(role: Role) -> Style {
+    current().style_for(role, &caps::current())
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::{Color, Modifier, Style};
+
+    use super::{Role, Theme};
+    use crate::caps::{Capabilities, ColorSupport};
+
+    fn caps(color: ColorSupport) -> Capabilities {
+        Capabilities {
+            color,
+            ..Capabilities::default()
+        }
+    }
+
+    #[test]
+    fn roles_are_adapted_to_the_terminal() {
+        let theme = Theme {
+            accent: Style::default().fg(Color::Rgb(250, 0, 0)),
+            ..Theme::dark()
+        };
+        assert_eq!(
+            theme.style_for(Role::Accent, &caps(ColorSupport::TrueColor)),
+            theme.accent
+        );
+        assert_eq!(
+            theme.style_for(Role::Accent, &caps(ColorSupport::Ansi16)),
+            Style::default().fg(Color::LightRed)
+        );
+    }
+
+    #[test]
+    fn no_color_falls_back_to_modifiers() {
+        let none = caps(ColorSupport::None);
+        let highlight = Theme::dark().style_for(Role::Highlight, &none);
+        assert!(highlight
+            .add_modifier
+            .contains(Modifier::BOLD | Modifier::REVERSED));
+        assert_eq!(highlight.bg, Some(Color::Reset));
+        let dimmed = Theme::light().style_for(Role::Dimmed, &none);
+        assert!(dimmed.add_modifier.contains(Modifier::DIM));
+    }
+}
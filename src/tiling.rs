@@ -0,0 +1,471 @@
+use std::collections::HashSet;
+
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::split::{Size, Split};
+
+/// Identifies a pane of a `Tiling`, handed out by `Tiling::add`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PaneId(usize);
+
+/// How a `Tiling` arranges its panes. Serialized in snake case, like
+/// `"grid"` or `{"master_stack": {"master_percent": 60}}`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Arrangement {
+    /// The first pane on the left taking `master_percent` of the width,
+    /// the others stacked on the right
+    MasterStack { master_percent: u16 },
+    /// Rows of equally sized panes, as close to square as possible
+    Grid,
+    /// Each pane takes half of the space left by the ones before it,
+    /// turning clockwise
+    Spiral,
+}
+
+impl Default for Arrangement {
+    fn default() -> Self {
+        Self::MasterStack { master_percent: 50 }
+    }
+}
+
+/// Direction to move the focus or a pane in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Toward {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Tiles any number of panes into an area, like a tiling window manager.
+/// Panes are kept in order, which decides where they go in the
+/// arrangement, and one of them has the focus. The panes, focus, zoom
+/// and arrangement can be serialized to restore them on the next start,
+/// the `PaneId`s stay the same. Deserializing fails on duplicate panes, a
+/// focus on a missing pane or ids that would be handed out again.
+/// ```
+/// use ratatui::layout::Rect;
+/// use tui_utils::tiling::{Arrangement, Tiling};
+///
+/// let mut tiling = Tiling::new(Arrangement::Grid);
+/// let logs = tiling.add();
+/// let metrics = tiling.add();
+/// for (pane, area) in tiling.layout(Rect::new(0, 0, 80, 24)) {
+///     // draw the widget of `pane` into `area`
+/// }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(try_from = "TilingState")]
+pub struct Tiling {
+    panes: Vec<PaneId>,
+    next_id: usize,
+    focused: Option<PaneId>,
+    zoomed: bool,
+    arrangement: Arrangement,
+    gap: u16,
+    /// Area of the last `layout`, used to find panes by direction
+    #[serde(skip)]
+    area: Rect,
+}
+
+/// What is deserialized into a `Tiling` after it is checked
+#[derive(Deserialize)]
+struct TilingState {
+    panes: Vec<PaneId>,
+    next_id: usize,
+    focused: Option<PaneId>,
+    zoomed: bool,
+    arrangement: Arrangement,
+    gap: u16,
+}
+
+#[derive(Error, Debug)]
+enum InvalidTiling {
+    #[error("pane {0} appears more than once")]
+    Duplicate(usize),
+    #[error("focused pane {0} does not exist")]
+    MissingFocus(usize),
+    #[error("next id {0} is already taken")]
+    TakenId(usize),
+}
+
+impl TryFrom<TilingState> for Tiling {
+    type Error = InvalidTiling;
+
+    fn try_from(state: TilingState) -> Result<Self, Self::Error> {
+        let mut seen = HashSet::new();
+        for &PaneId(id) in &state.panes {
+            if !seen.insert(id) {
+                return Err(InvalidTiling::Duplicate(id));
+            }
+            if id >= state.next_id {
+                return Err(InvalidTiling::TakenId(state.next_id));
+            }
+        }
+        match state.focused {
+            Some(PaneId(id)) if !seen.contains(&id) => Err(InvalidTiling::MissingFocus(id)),
+            _ => Ok(Self {
+                panes: state.panes,
+                next_id: state.next_id,
+                focused: state.focused,
+                zoomed: state.zoomed,
+                arrangement: state.arrangement,
+                gap: state.gap,
+                area: Rect::default(),
+            }),
+        }
+    }
+}
+
+impl Tiling {
+    pub fn new(arrangement: Arrangement) -> Self {
+        Self {
+            arrangement,
+            ..Self::default()
+        }
+    }
+
+    /// Empty cells between neighbouring panes
+    pub fn gap(mut self, gap: u16) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    pub fn arrangement(&self) -> Arrangement {
+        self.arrangement
+    }
+
+    pub fn set_arrangement(&mut self, arrangement: Arrangement) {
+        self.arrangement = arrangement;
+    }
+
+    /// Panes in the order they are arranged in
+    pub fn panes(&self) -> &[PaneId] {
+        &self.panes
+    }
+
+    pub fn focused(&self) -> Option<PaneId> {
+        self.focused
+    }
+
+    /// Add a pane after the focused one and focus it
+    pub fn add(&mut self) -> PaneId {
+        let id = PaneId(self.next_id);
+        self.next_id += 1;
+        let at = self.focused_index().map_or(self.panes.len(), |i| i + 1);
+        self.panes.insert(at, id);
+        self.focused = Some(id);
+        id
+    }
+
+    /// Remove `pane`. If it had the focus, the pane before it gets it, or
+    /// the new first pane if it was the first. Returns whether the pane existed.
+    pub fn remove(&mut self, pane: PaneId) -> bool {
+        let Some(index) = self.index_of(pane) else {
+            return false;
+        };
+        self.panes.remove(index);
+        if self.focused == Some(pane) {
+            self.focused = self.panes.get(index.saturating_sub(1)).copied();
+            self.zoomed = false;
+        }
+        true
+    }
+
+    /// Focus `pane` if it exists
+    pub fn focus(&mut self, pane: PaneId) -> bool {
+        let exists = self.index_of(pane).is_some();
+        if exists {
+            self.focused = Some(pane);
+        }
+        exists
+    }
+
+    /// Focus the closest pane `toward` the focused one in the last layout.
+    /// Returns whether there was one.
+    pub fn focus_toward(&mut self, toward: Toward) -> bool {
+        match self.neighbour(toward) {
+            Some(pane) => self.focus(pane),
+            None => false,
+        }
+    }
+
+    /// Swap the focused pane with the closest one `toward` it in the last
+    /// layout. The focus moves along with the pane.
+    pub fn swap_toward(&mut self, toward: Toward) -> bool {
+        let (Some(focused), Some(other)) = (self.focused, self.neighbour(toward)) else {
+            return false;
+        };
+        self.swap(focused, other)
+    }
+
+    /// Swap the places of two panes
+    pub fn swap(&mut self, a: PaneId, b: PaneId) -> bool {
+        match (self.index_of(a), self.index_of(b)) {
+            (Some(a), Some(b)) => {
+                self.panes.swap(a, b);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Show only the focused pane, taking the whole area, or go back to
+    /// the arrangement
+    pub fn toggle_zoom(&mut self) {
+        self.zoomed = !self.zoomed && self.focused.is_some();
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        self.zoomed
+    }
+
+    /// Areas of the panes within `area`, in pane order. While zoomed only
+    /// the focused pane is returned.
+    pub fn layout(&mut self, area: Rect) -> Vec<(PaneId, Rect)> {
+        self.area = area;
+        match (self.zoomed, self.focused) {
+            (true, Some(focused)) => vec![(focused, area)],
+            _ => self.arrange(area),
+        }
+    }
+
+    fn arrange(&self, area: Rect) -> Vec<(PaneId, Rect)> {
+        let areas = match (self.panes.len(), self.arrangement) {
+            (0, _) => Vec::new(),
+            (1, _) => vec![area],
+            (n, Arrangement::MasterStack { master_percent }) => {
+                let halves = Split::columns([Size::Percentage(master_percent), Size::Fill(1)])
+                    .gap(self.gap)
+                    .split(area);
+                let stack = Split::rows(vec![Size::Fill(1); n - 1])
+                    .gap(self.gap)
+                    .split(halves[1]);
+                std::iter::once(halves[0])
+                    .chain(stack.iter().copied())
+                    .collect()
+            }
+            (n, Arrangement::Grid) => self.grid(area, n),
+            (n, Arrangement::Spiral) => self.spiral(area, n),
+        };
+        self.panes.iter().copied().zip(areas).collect()
+    }
+
+    fn grid(&self, area: Rect, n: usize) -> Vec<Rect> {
+        let columns = (1..=n).find(|c| c * c >= n).unwrap_or(1);
+        let rows = n.div_ceil(columns);
+        let row_areas = Split::rows(vec![Size::Fill(1); rows])
+            .gap(self.gap)
+            .split(area);
+        row_areas
+            .iter()
+            .enumerate()
+            .flat_map(|(row, row_area)| {
+                // the last row may have fewer panes, which share its width
+                let count = columns.min(n - row * columns);
+                Split::columns(vec![Size::Fill(1); count])
+                    .gap(self.gap)
+                    .split(*row_area)
+                    .to_vec()
+            })
+            .collect()
+    }
+
+    fn spiral(&self, mut area: Rect, n: usize) -> Vec<Rect> {
+        let halves = [Size::Fill(1), Size::Fill(1)];
+        let mut areas = Vec::with_capacity(n);
+        for i in 0..n - 1 {
+            let (pane, rest) = match i % 4 {
+                0 => split_pair(Split::columns(halves), self.gap, area, false),
+                1 => split_pair(Split::rows(halves), self.gap, area, false),
+                2 => split_pair(Split::columns(halves), self.gap, area, true),
+                _ => split_pair(Split::rows(halves), self.gap, area, true),
+            };
+            areas.push(pane);
+            area = rest;
+        }
+        areas.push(area);
+        areas
+    }
+
+    /// The pane closest to the focused one in direction `toward`, by the
+    /// distance between their edges and then between their centers
+    fn neighbour(&self, toward: Toward) -> Option<PaneId> {
+        let layout = self.arrange(self.area);
+        let from = layout.iter().find(|(p, _)| Some(*p) == self.focused)?.1;
+        let center = |r: Rect| {
+            (
+                r.x as i32 * 2 + r.width as i32,
+                r.y as i32 * 2 + r.height as i32,
+            )
+        };
+        let (cx, cy) = center(from);
+        layout
+            .iter()
+            .filter_map(|&(pane, r)| {
+                let (x, y) = center(r);
+                let (edge, across) = match toward {
+                    Toward::Left if r.right() <= from.left() => (from.left() - r.right(), cy - y),
+                    Toward::Right if r.left() >= from.right() => (r.left() - from.right(), cy - y),
+                    Toward::Up if r.bottom() <= from.top() => (from.top() - r.bottom(), cx - x),
+                    Toward::Down if r.top() >= from.bottom() => (r.top() - from.bottom(), cx - x),
+                    _ => return None,
+                };
+                Some(((edge, across.abs()), pane))
+            })
+            .min()
+            .map(|(_, pane)| pane)
+    }
+
+    fn index_of(&self, pane: PaneId) -> Option<usize> {
+        self.panes.iter().position(|&p| p == pane)
+    }
+
+    fn focused_index(&self) -> Option<usize> {
+        self.index_of(self.focused?)
+    }
+}
+
+/// Split `area` in two and return the pane and what is left. `reverse`
+/// puts the pane second.
+fn split_pair(split: Split, gap: u16, area: Rect, reverse: bool) -> (Rect, Rect) {
+    let parts = split.gap(gap).split(area);
+    match reverse {
+        false => (parts[0], parts[1]),
+        true => (parts[1], parts[0]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+
+    use super::{Arrangement, Tiling, Toward};
+
+    fn areas(tiling: &mut Tiling, area: Rect) -> Vec<Rect> {
+        tiling.layout(area).into_iter().map(|(_, r)| r).collect()
+    }
+
+    #[test]
+    fn master_and_stack() {
+        let mut tiling = Tiling::new(Arrangement::MasterStack { master_percent: 60 });
+        for _ in 0..3 {
+            tiling.add();
+        }
+        assert_eq!(
+            areas(&mut tiling, Rect::new(0, 0, 100, 20)),
+            [
+                Rect::new(0, 0, 60, 20),
+                Rect::new(60, 0, 40, 10),
+                Rect::new(60, 10, 40, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn grid_fills_the_last_row() {
+        let mut tiling = Tiling::new(Arrangement::Grid);
+        for _ in 0..5 {
+            tiling.add();
+        }
+        let areas = areas(&mut tiling, Rect::new(0, 0, 90, 20));
+        assert_eq!(areas[0], Rect::new(0, 0, 30, 10));
+        assert_eq!(areas[3], Rect::new(0, 10, 45, 10));
+        assert_eq!(areas[4], Rect::new(45, 10, 45, 10));
+    }
+
+    #[test]
+    fn spiral_halves_the_rest() {
+        let mut tiling = Tiling::new(Arrangement::Spiral);
+        for _ in 0..4 {
+            tiling.add();
+        }
+        assert_eq!(
+            areas(&mut tiling, Rect::new(0, 0, 80, 40)),
+            [
+                Rect::new(0, 0, 40, 40),
+                Rect::new(40, 0, 40, 20),
+                Rect::new(60, 20, 20, 20),
+                Rect::new(40, 20, 20, 20),
+            ]
+        );
+    }
+
+    #[test]
+    fn focus_and_swap_by_direction() {
+        let mut tiling = Tiling::new(Arrangement::default());
+        let master = tiling.add();
+        let top = tiling.add();
+        let bottom = tiling.add();
+        tiling.layout(Rect::new(0, 0, 100, 20));
+
+        assert_eq!(tiling.focused(), Some(bottom));
+        assert!(tiling.focus_toward(Toward::Up));
+        assert_eq!(tiling.focused(), Some(top));
+        assert!(!tiling.focus_toward(Toward::Right));
+        assert!(tiling.focus_toward(Toward::Left));
+        assert_eq!(tiling.focused(), Some(master));
+
+        assert!(tiling.swap_toward(Toward::Right));
+        assert_eq!(tiling.panes(), [top, master, bottom]);
+        assert_eq!(tiling.focused(), Some(master));
+    }
+
+    #[test]
+    fn remove_and_zoom() {
+        let mut tiling = Tiling::new(Arrangement::Grid);
+        let first = tiling.add();
+        let second = tiling.add();
+        tiling.toggle_zoom();
+        let area = Rect::new(0, 0, 50, 10);
+        assert_eq!(tiling.layout(area), [(second, area)]);
+
+        assert!(tiling.remove(second));
+        assert!(!tiling.is_zoomed());
+        assert_eq!(tiling.focused(), Some(first));
+        assert!(!tiling.remove(second));
+        assert_eq!(tiling.layout(area), [(first, area)]);
+
+        // without a pane before it, the focus moves to the new first pane
+        let third = tiling.add();
+        tiling.focus(first);
+        assert!(tiling.remove(first));
+        assert_eq!(tiling.focused(), Some(third));
+    }
+
+    #[test]
+    fn tiling_state_round_trips() {
+        let mut tiling = Tiling::new(Arrangement::MasterStack { master_percent: 60 });
+        let first = tiling.add();
+        tiling.add();
+        tiling.focus(first);
+        tiling.toggle_zoom();
+
+        let json = serde_json::to_string(&tiling).unwrap();
+        assert!(json.contains(r#""arrangement":{"master_stack":{"master_percent":60}}"#));
+        let mut restored: Tiling = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.panes(), tiling.panes());
+        assert_eq!(restored.focused(), Some(first));
+        assert!(restored.is_zoomed());
+        // ids keep counting from where they were
+        assert!(!tiling.panes().contains(&restored.add()));
+    }
+
+    #[test]
+    fn invalid_state_is_rejected() {
+        let state = |panes: &str, next_id: usize, focused: &str| {
+            let json = format!(
+                r#"{{"panes":{panes},"next_id":{next_id},"focused":{focused},"zoomed":false,"arrangement":"grid","gap":0}}"#
+            );
+            serde_json::from_str::<Tiling>(&json)
+        };
+        assert!(state("[0,1]", 2, "1").is_ok());
+        assert!(state("[]", 0, "null").is_ok());
+        assert!(state("[0,0]", 2, "0").is_err());
+        assert!(state("[0,1]", 2, "5").is_err());
+        assert!(state("[0,1]", 1, "0").is_err());
+    }
+}
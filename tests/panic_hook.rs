@@ -0,0 +1,169 @@
+//! The panic hook is process wide, so these checks run in their own binary
+//! without the test harness, one after another on the main thread.
+
+use std::{
+    env,
+    io::{self, Write},
+    panic,
+    process::Command,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use ratatui::{
+    backend::{Backend, TestBackend},
+    buffer::Cell,
+    layout::Rect,
+};
+use tui_utils::{
+    backend::{EventInput, Restorer, TermBackend},
+    event::Event,
+    term::{self, InitOptions, Output, TermError},
+};
+
+/// Tells the child process which case to run
+const CHILD: &str = "TUI_UTILS_PANIC_CHILD";
+/// Written to stderr by `FakeBackend`'s restorer
+const RESTORED: &str = "\x1b[?1049l";
+/// Set by `FakeBackend`'s restorer
+static RESTORER_RAN: AtomicBool = AtomicBool::new(false);
+
+fn main() {
+    match env::var(CHILD).as_deref() {
+        Ok("no_guard") => {
+            term::install_panic_hook();
+            panic!("no terminal was set up");
+        }
+        Ok("stderr_guard") => {
+            let _guard = InitOptions::default()
+                .output(Output::Stderr)
+                .init_with(FakeBackend(TestBackend::new(10, 2)))
+                .unwrap();
+            panic!("drawing to stderr");
+        }
+        _ => {
+            panic_without_guard_writes_nothing();
+            panic_restores_on_the_chosen_output();
+            panic_hook_restores_before_previous_hook();
+        }
+    }
+}
+
+/// Run this binary with `case` and return its stdout and stderr
+fn run_child(case: &str) -> (String, String) {
+    let output = Command::new(env::current_exe().unwrap())
+        .env(CHILD, case)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    (
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+fn panic_without_guard_writes_nothing() {
+    let (stdout, stderr) = run_child("no_guard");
+    assert_eq!(stdout, "");
+    assert!(stderr.contains("no terminal was set up"));
+    assert!(!stderr.contains('\x1b'));
+}
+
+fn panic_restores_on_the_chosen_output() {
+    let (stdout, stderr) = run_child("stderr_guard");
+    assert_eq!(stdout, "");
+    let restored = stderr.find(RESTORED).expect("terminal was not restored");
+    let message = stderr.find("drawing to stderr").unwrap();
+    assert!(restored < message);
+}
+
+fn panic_hook_restores_before_previous_hook() {
+    static SAW_RESTORED: AtomicBool = AtomicBool::new(false);
+    panic::set_hook(Box::new(|_| {
+        // the terminal has to be restored by the time the message is printed
+        SAW_RESTORED.store(RESTORER_RAN.load(Ordering::SeqCst), Ordering::SeqCst);
+    }));
+    let guard = InitOptions::default()
+        .output(Output::Tty)
+        .init_with(FakeBackend(TestBackend::new(10, 2)))
+        .unwrap();
+
+    let outcome = panic::catch_unwind(|| panic!("boom"));
+    let _ = panic::take_hook();
+    drop(guard);
+
+    assert!(outcome.is_err());
+    assert!(SAW_RESTORED.load(Ordering::SeqCst));
+}
+
+/// Backend whose restorer records that it ran and writes the end of the
+/// alternate screen to stderr when that is the output the guard hands it
+struct FakeBackend(TestBackend);
+
+#[derive(Default)]
+struct NoInput;
+
+impl EventInput for NoInput {
+    fn poll_event(&mut self, _timeout: Duration) -> Result<Option<Event>, TermError> {
+        Ok(None)
+    }
+}
+
+impl TermBackend for FakeBackend {
+    type Input = NoInput;
+
+    fn write_sequence(&mut self, _sequence: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_raw_mode(&mut self, _enable: bool) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn restorer(&self, output: Output) -> Option<Restorer> {
+        Some(Box::new(move |_| {
+            RESTORER_RAN.store(true, Ordering::SeqCst);
+            match output {
+                Output::Stderr => io::stderr().write_all(RESTORED.as_bytes()),
+                _ => Ok(()),
+            }
+        }))
+    }
+}
+
+impl Backend for FakeBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        self.0.draw(content)
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.0.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.0.show_cursor()
+    }
+
+    fn get_cursor(&mut self) -> io::Result<(u16, u16)> {
+        self.0.get_cursor()
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
+        self.0.set_cursor(x, y)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.0.clear()
+    }
+
+    fn size(&self) -> io::Result<Rect> {
+        self.0.size()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}